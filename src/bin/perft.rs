@@ -0,0 +1,68 @@
+//! Runs perft on a position and reports the node count and speed
+//!
+//! Usage: `perft "<fen>" <depth>`
+
+use std::env;
+use std::process;
+use std::time::{Duration, Instant};
+
+use rustmonkey::board::{Board, FenError};
+use rustmonkey::helper::tables;
+use rustmonkey::movegen::perft;
+
+/// Runs perft from `fen` to `depth`, returning the node count and the time
+/// it took
+fn run(fen: &str, depth: u32) -> Result<(u64, Duration), FenError> {
+    tables::init();
+    let board = Board::from_fen(fen)?;
+    let start = Instant::now();
+    let nodes = perft(&board, depth);
+    Ok((nodes, start.elapsed()))
+}
+
+/// Returns the number of nodes searched per second
+fn nps(nodes: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0f64 { (nodes as f64 / secs) as u64 } else { 0u64 }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} \"<fen>\" <depth>", args[0]);
+        process::exit(1);
+    }
+    let depth = match args[2].parse::<u32>() {
+        Ok(depth) => depth,
+        Err(_) => {
+            eprintln!("Invalid depth: {}", args[2]);
+            process::exit(1);
+        }
+    };
+    match run(&args[1], depth) {
+        Ok((nodes, elapsed)) => {
+            println!("nodes: {}", nodes);
+            println!("time:  {:.3}s", elapsed.as_secs_f64());
+            println!("nps:   {}", nps(nodes, elapsed));
+        }
+        Err(e) => {
+            eprintln!("Invalid FEN: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let (nodes, _) = run(fen, 2).unwrap();
+        assert_eq!(nodes, 400);
+        assert!(run("8/8/8/8/8/8/8/8 w - - 0 1", 2).is_err());
+        assert_eq!(nps(1000, Duration::from_millis(500)), 2000);
+    }
+}
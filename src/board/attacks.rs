@@ -0,0 +1,244 @@
+//! Attack queries on a [`Board`]
+//!
+//! All of these rely on the lookup tables in [`crate::helper::tables`], so
+//! [`tables::init`] must have been called beforehand.
+
+use super::*;
+use crate::helper::tables;
+
+impl Board {
+    /// Returns the squares attacked by the piece on `sq`
+    ///
+    /// Sliding pieces are blocked by the current occupancy. An empty square
+    /// attacks nothing.
+    pub fn attacks_from(&self, sq: Square) -> Bitboard {
+        match self.get(sq) {
+            Piece::Null => panic!("Attempted to get attacks from Piece::Null"),
+            Piece::Empty => Bitboard::EMPTY,
+            Piece::Pc(w, PieceType::P) => Board::pawn_attacks(sq, w),
+            Piece::Pc(_, PieceType::N) => tables::get_knight_moves(sq),
+            Piece::Pc(_, PieceType::B) => tables::get_bishop_attacks_magic(sq, self.get_all()),
+            Piece::Pc(_, PieceType::R) => tables::get_rook_attacks_magic(sq, self.get_all()),
+            Piece::Pc(_, PieceType::Q) => tables::get_queen_attacks_magic(sq, self.get_all()),
+            Piece::Pc(_, PieceType::K) => tables::get_king_moves(sq),
+        }
+    }
+
+    /// Returns the squares a pawn of `w` on `sq` attacks
+    ///
+    /// Our pawns attack up the board and theirs attack down the board
+    pub fn pawn_attacks(sq: Square, w: Whose) -> Bitboard {
+        match w {
+            Whose::Ours => tables::get_pawn_attacks(sq),
+            Whose::Theirs => {
+                let mut attacks = tables::get_pawn_attacks(sq.flipped());
+                attacks.flip();
+                attacks
+            }
+        }
+    }
+
+    /// Returns the squares of all of `w`'s pieces that attack `sq`
+    pub fn attackers_to(&self, sq: Square, w: Whose) -> Bitboard {
+        self.attackers_to_through(sq, w, self.get_all())
+    }
+
+    /// Helper function, returns the squares of `w`'s pieces within `occ`
+    /// that attack `sq` as if only the squares in `occ` were occupied
+    /// 
+    /// Removing pieces from `occ` reveals the sliders behind them (x-rays)
+    pub(super) fn attackers_to_through(&self, sq: Square, w: Whose, occ: Bitboard) -> Bitboard {
+        let mut them = w;
+        them.flip();
+        let pawns = self.get_pieces(Piece::Pc(w, PieceType::P));
+        let knights = self.get_pieces(Piece::Pc(w, PieceType::N));
+        let queens = self.get_pieces(Piece::Pc(w, PieceType::Q));
+        let diagonals = self.get_pieces(Piece::Pc(w, PieceType::B)) | queens;
+        let orthogonals = self.get_pieces(Piece::Pc(w, PieceType::R)) | queens;
+        let king = self.get_pieces(Piece::Pc(w, PieceType::K));
+        // A pawn of `w` attacks `sq` iff a pawn of `them` on `sq` would 
+        // attack the pawn's square
+        ((Board::pawn_attacks(sq, them) & pawns)
+            | (tables::get_knight_moves(sq) & knights)
+            | (tables::get_bishop_attacks_magic(sq, occ) & diagonals)
+            | (tables::get_rook_attacks_magic(sq, occ) & orthogonals)
+            | (tables::get_king_moves(sq) & king))
+            & occ
+    }
+
+    /// Returns whether or not any of `w`'s pieces attack `sq`
+    pub fn is_attacked(&self, sq: Square, w: Whose) -> bool {
+        !self.attackers_to(sq, w).is_empty()
+    }
+
+    /// Returns whether or not our king is in check
+    /// 
+    /// A board without our king (e.g. after it exploded in
+    /// [`Atomic`](super::variant::Variant::Atomic) chess) is never in check
+    pub fn is_in_check(&self) -> bool {
+        match self.kings[Whose::Ours as usize] {
+            Square::Null => false,
+            king => self.is_attacked(king, Whose::Theirs),
+        }
+    }
+
+    /// Returns the squares of their pieces giving check to our king
+    /// 
+    /// Two checkers (double check) means only a king move can get out of
+    /// check. A board without our king has no checkers.
+    pub fn checkers(&self) -> Bitboard {
+        match self.kings[Whose::Ours as usize] {
+            Square::Null => Bitboard::EMPTY,
+            king => self.attackers_to(king, Whose::Theirs),
+        }
+    }
+
+    /// Returns the squares of our pieces that are pinned to our king
+    /// 
+    /// A piece is pinned if it is the only piece between our king and one of
+    /// their sliders that would otherwise attack the king. A pinned piece may
+    /// only move along the line through the king and the pinning slider.
+    pub fn pinned(&self) -> Bitboard {
+        let king = self.kings[Whose::Ours as usize];
+        if king.is_null() {
+            return Bitboard::EMPTY;
+        }
+        let queens = self.get_pieces(Piece::Pc(Whose::Theirs, PieceType::Q));
+        let diagonals = self.get_pieces(Piece::Pc(Whose::Theirs, PieceType::B)) | queens;
+        let orthogonals = self.get_pieces(Piece::Pc(Whose::Theirs, PieceType::R)) | queens;
+        // Sliders that would see the king on an empty board
+        let snipers = (tables::get_bishop_attacks_magic(king, Bitboard::EMPTY) & diagonals)
+            | (tables::get_rook_attacks_magic(king, Bitboard::EMPTY) & orthogonals);
+        let occ = self.get_all();
+        let mut pinned = Bitboard::EMPTY;
+        for sniper in snipers {
+            let blockers = tables::get_between(king, sniper) & occ;
+            if blockers.is_singular() {
+                pinned = pinned | (blockers & self.get_whose(Whose::Ours));
+            }
+        }
+        pinned
+    }
+
+    /// Returns every square attacked by at least one of `w`'s pieces
+    /// 
+    /// Sliders are blocked by the current occupancy, and squares holding
+    /// `w`'s own pieces count as attacked (i.e. defended)
+    pub fn attacks_by(&self, w: Whose) -> Bitboard {
+        let mut attacks = self.pawn_attacks_by(w);
+        for sq in self.get_whose(w).without(self.get_pieces(Piece::Pc(w, PieceType::P))) {
+            attacks = attacks | self.attacks_from(sq);
+        }
+        attacks
+    }
+
+    /// Returns every square attacked by one of `w`'s pawns
+    pub fn pawn_attacks_by(&self, w: Whose) -> Bitboard {
+        let mut attacks = Bitboard::EMPTY;
+        for sq in self.get_pieces(Piece::Pc(w, PieceType::P)) {
+            attacks = attacks | Board::pawn_attacks(sq, w);
+        }
+        attacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_attacks_from() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/3p4/4N3/8/8/R3K3 w Q - 0 1");
+        let knight = bd.attacks_from(Square::from(File::E, Rank::Fourth));
+        assert_eq!(knight.pop_count(), 8);
+        let rook = bd.attacks_from(Square::from(File::A, Rank::First));
+        assert_eq!(rook.pop_count(), 7 + 4);
+        let pawn = bd.attacks_from(Square::from(File::D, Rank::Fifth));
+        assert!(pawn.get(Square::from(File::E, Rank::Fourth)));
+        assert!(pawn.get(Square::from(File::C, Rank::Fourth)));
+        assert_eq!(pawn.pop_count(), 2);
+        assert!(bd.attacks_from(Square::from(File::H, Rank::Eighth)).is_empty());
+    }
+
+    #[test]
+    fn test_is_attacked() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1");
+        assert!(bd.is_in_check());
+        assert!(bd.is_attacked(Square::from(File::C, Rank::First), Whose::Theirs));
+        assert!(!bd.is_attacked(Square::from(File::D, Rank::First), Whose::Theirs));
+        bd.board_from_fen("4k3/8/8/8/8/8/3P4/4K3 b - - 0 1");
+        assert!(!bd.is_in_check());
+        bd.board_from_fen("4k3/8/8/b7/8/8/8/4K3 w - - 0 1");
+        assert!(bd.is_in_check());
+        bd.board_from_fen("4k3/8/8/b7/8/2P5/8/4K3 w - - 0 1");
+        assert!(!bd.is_in_check());
+        bd.board_from_fen("4k3/8/8/8/8/8/8/r3K3 w - - 0 1");
+        assert!(bd.is_in_check());
+        assert_eq!(bd.attackers_to(Square::from(File::E, Rank::First), Whose::Theirs),
+                   Square::from(File::A, Rank::First).to_bitboard());
+    }
+
+    #[test]
+    fn test_pinned() {
+        tables::init();
+        let mut bd = Board::new();
+        // The knight is pinned by the bishop, so it can't move at all
+        bd.board_from_fen("4k3/8/8/b7/8/2N5/8/4K3 w - - 0 1");
+        let c3 = Square::from(File::C, Rank::Third);
+        assert_eq!(bd.pinned(), c3.to_bitboard());
+        assert!(generate_legal(&bd).iter().all(|m| m.from != c3));
+        // Two pieces in the way means neither is pinned, and their pieces
+        // in the way don't pin anything
+        bd.board_from_fen("4k3/8/8/b7/1P6/2N5/8/4K3 w - - 0 1");
+        assert!(bd.pinned().is_empty());
+        bd.board_from_fen("4k3/8/8/b7/1p6/2N5/8/4K3 w - - 0 1");
+        assert!(bd.pinned().is_empty());
+        // Pinned along a file and a diagonal at once
+        bd.board_from_fen("4r3/8/8/q7/8/2B5/4R3/4K3 w - - 0 1");
+        assert_eq!(bd.pinned().pop_count(), 2);
+    }
+
+    #[test]
+    fn test_attacks_by() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let attacks = bd.attacks_by(Whose::Ours);
+        // The whole third and second ranks, and the first apart from the corners
+        for rank in [Rank::Second, Rank::Third] {
+            let rank_bb = tables::get_rank_bb(rank);
+            assert_eq!(attacks & rank_bb, rank_bb);
+        }
+        assert!(!attacks.get(Square::from(File::A, Rank::First)));
+        assert!(!attacks.get(Square::from(File::H, Rank::First)));
+        assert_eq!(attacks.pop_count(), 8 + 8 + 6);
+        // Black's attacks are the same, seen from the other side
+        let mut theirs = bd.attacks_by(Whose::Theirs);
+        theirs.flip();
+        assert_eq!(theirs, attacks);
+        for sq in Square::all() {
+            assert_eq!(attacks.get(sq), bd.is_attacked(sq, Whose::Ours));
+        }
+    }
+
+    #[test]
+    fn test_checkers() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(bd.checkers().is_empty());
+        // Nd6+ uncovered the rook on the e-file: double check
+        bd.board_from_fen("4k3/8/3N4/8/8/8/8/4R1K1 b - - 0 1");
+        let checkers = bd.checkers();
+        assert_eq!(checkers.pop_count(), 2);
+        assert!(checkers.get(Square::from(File::D, Rank::Sixth).flipped()));
+        assert!(checkers.get(Square::from(File::E, Rank::First).flipped()));
+        let king = bd.kings[Whose::Ours as usize];
+        assert!(generate_legal(&bd).iter().all(|m| m.from == king));
+    }
+}
@@ -4,6 +4,7 @@
 //! 
 //! # Example: Placing two kings on a bitboard
 //! ```rust
+//! # use rustmonkey::board::bits::{Bitboard, File, Rank, Square};
 //! // Create a square representing e1 (the white king)
 //! let whiteKing = Square::from(File::E, Rank::First);
 //! 
@@ -28,12 +29,37 @@
 //! . . . . x . . .
 //! */ 
 //! ```
+//!
+//! # Null values
+//!
+//! [`Square::Null`], [`Bitboard::Null`], and friends stand for "not set".
+//! Most methods panic when given one, since that is always a bug inside the
+//! engine. Code handling outside input can use the `try_` variants (e.g.
+//! [`Square::try_val`] or [`Bitboard::try_set`]) instead, which return
+//! `None` rather than panicking. Constructors such as [`Square::new`] and
+//! [`Square::from`], along with `is_null`, never panic.
 
 use std::ops;
 use std::cmp::Ordering;
 use std::fmt;
 
 use super::util::*;
+use crate::helper::Direction;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The value rejected by one of the `TryFrom` conversions (e.g. a square
+/// index above 63 or a file letter past `'h'`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRange<T>(pub T);
+
+impl<T: fmt::Debug> fmt::Display for OutOfRange<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} is out of range", self.0)
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for OutOfRange<T> {}
 
 /// The rows of a chess board
 #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
@@ -51,7 +77,7 @@ pub enum Rank {
 
 impl Rank {
     /// Number of ranks
-    const COUNT: usize = 8;
+    pub const COUNT: usize = 8;
 
     /// Maps integers from zero to seven inclusive to ranks
     pub fn convert(x: isize) -> Rank {
@@ -97,6 +123,18 @@ impl Rank {
     // }
 }
 
+/// Reads a rank from its digit, `'1'` to `'8'`
+impl TryFrom<char> for Rank {
+    type Error = OutOfRange<char>;
+
+    fn try_from(c: char) -> Result<Rank, Self::Error> {
+        match c {
+            '1'..='8' => Ok(Rank::convert(c as isize - '1' as isize)),
+            _ => Err(OutOfRange(c)),
+        }
+    }
+}
+
 /// The columns of a chess board
 #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub enum File { 
@@ -113,7 +151,7 @@ pub enum File {
 
 impl File {
     /// Number of files
-    const COUNT: usize = 8;
+    pub const COUNT: usize = 8;
 
     /// Maps integers from zero to seven inclusive to files
     pub fn convert(x: isize) -> File {
@@ -159,9 +197,22 @@ impl File {
     // }
 }
 
+/// Reads a file from its letter, `'a'` to `'h'`
+impl TryFrom<char> for File {
+    type Error = OutOfRange<char>;
+
+    fn try_from(c: char) -> Result<File, Self::Error> {
+        match c {
+            'a'..='h' => Ok(File::convert(c as isize - 'a' as isize)),
+            _ => Err(OutOfRange(c)),
+        }
+    }
+}
+
 /// A value ranging from 0 to 64, representing the squares from a1-h8 in 
 /// rank-major order
 #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Square {
     /// An invalid square (typically the result of unsafe operations)
     Null,
@@ -170,11 +221,36 @@ pub enum Square {
     Sq(u8),
 }
 
+/// Displays a square in algebraic notation (e.g. `e4`)
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             Square::Null => write!(f, "Null"),
-            Square::Sq(s) => write!(f, "Sq({})", s),
+            Square::Sq(_) => {
+                let file = match self.file() {
+                    File::A => 'a',
+                    File::B => 'b',
+                    File::C => 'c',
+                    File::D => 'd',
+                    File::E => 'e',
+                    File::F => 'f',
+                    File::G => 'g',
+                    File::H => 'h',
+                    File::Null => 'X'
+                };
+                let rank = match self.rank() {
+                    Rank::First => '1',
+                    Rank::Second => '2',
+                    Rank::Third => '3',
+                    Rank::Fourth => '4',
+                    Rank::Fifth => '5',
+                    Rank::Sixth => '6',
+                    Rank::Seventh => '7',
+                    Rank::Eighth => '8',
+                    Rank::Null => 'X'
+                };
+                write!(f, "{}{}", file, rank)
+            }
         }
     }
 }
@@ -209,12 +285,24 @@ impl Square {
         }
     }
 
+    /// Like [`val`](Square::val), but returns `None` for `Null`
+    pub fn try_val(&self) -> Option<u8> {
+        match *self {
+            Square::Null => None,
+            Square::Sq(s) => Some(s),
+        }
+    }
+
     /// Checks if a square is Square::Null
     pub fn is_null(&self) -> bool {
         *self == Square::Null
     }
 
     /// Creates an iterator of squares from two squares
+    /// 
+    /// Like Rust's `a..b`, the range is half-open: `sq2` itself is never
+    /// included (see [`range_from_int`](Square::range_from_int) for the
+    /// details and for ranges that reach h8)
     pub fn range(sq1: Square, sq2: Square) -> SquareRange {
         if sq1.is_null() {
             panic!("Attempted to start range with null square");
@@ -222,34 +310,30 @@ impl Square {
         if sq2.is_null() {
             panic!("Attempted to end range with null square");
         }
-        let (s1, s2) = (sq1.val(), sq2.val());
-        match s1.cmp(&s2) {
-            Ordering::Equal => panic!("Attempted to make empty range"),
-            _ => {
-                SquareRange {
-                    current: s1,
-                    end: s2,
-                }
-            }
-        }
+        Square::range_from_int(sq1.val(), sq2.val())
+    }
+
+    /// Creates an iterator over all 64 squares, from a1 to h8
+    pub fn all() -> impl Iterator<Item = Square> {
+        (Square::MIN_VAL..=Square::MAX_VAL).map(Square::Sq)
     }
 
     /// Creates an iterator of squares from an integer range
+    /// 
+    /// The range covers the half-open interval between the two bounds, i.e.
+    /// the lower bound is included and the upper bound is not, so either
+    /// bound may be 64. It runs upwards if `s1 < s2` and downwards if
+    /// `s1 > s2`, and is empty if they are equal. For example,
+    /// `range_from_int(0, 64)` yields a1 up to h8 and `range_from_int(64, 0)`
+    /// yields h8 down to a1.
     pub fn range_from_int(s1: u8, s2: u8) -> SquareRange {
-        if s1 > Square::MAX_VAL {
-            panic!("Attempted to start range with invalid square value");
+        let end = Square::MAX_VAL + 1;
+        if s1 > end || s2 > end {
+            panic!("Attempted to make range with invalid square value");
         }
-        if s2 != 64u8 && s2 > Square::MAX_VAL {
-            panic!("Attempted to end range with invalid square value");
-        }
-        match s1.cmp(&s2) {
-            Ordering::Equal => panic!("Attempted to make empty range"),
-            _ => {
-                SquareRange {
-                    current: s1,
-                    end: s2,
-                }
-            }
+        SquareRange {
+            current: s1,
+            end: s2,
         }
     }
 
@@ -293,6 +377,22 @@ impl Square {
         }
     }
 
+    /// Like [`rank`](Square::rank), but returns `None` for `Null`
+    pub fn try_rank(&self) -> Option<Rank> {
+        match self {
+            Square::Null => None,
+            _ => Some(self.rank()),
+        }
+    }
+
+    /// Like [`file`](Square::file), but returns `None` for `Null`
+    pub fn try_file(&self) -> Option<File> {
+        match self {
+            Square::Null => None,
+            _ => Some(self.file()),
+        }
+    }
+
     /// Returns the square above
     pub fn rank_up(&self) -> Square {
         match *self {
@@ -348,55 +448,26 @@ impl Square {
         }
     }
 
-    /// Returns the square offset by nmber of ranks and files
+    /// Returns the square offset by `dx` files and `dy` ranks
+    /// 
+    /// Returns [`Square::Null`] if the file or the rank would leave the
+    /// board (rather than wrapping around to the other side)
     pub fn offset(&self, dx: i8, dy: i8) -> Square {
         match *self {
             Square::Null => panic!("Attempted to get offset from Square::Null"),
-            Square::Sq(s) => {
-                let f = self.file();
-                let r = self.rank();
-                let f_val = (f as i8) + dx;
-                let r_val = (r as i8) + dy;
-                let f_ = File::convert(f_val as isize);
-                let r_ = Rank::convert(r_val as isize);
-                Square::from(f_, r_)
-            }
-        }
-    }
-
-    /// Returns the string representation of the square
-    pub fn to_string(&self) -> String {
-        match self {
-            Square::Null => panic!("Attempted to convert Square::Null to string"),
             Square::Sq(_) => {
-                let mut str: String = String::new();
-                match self.file() {
-                    File::A => str.push('a'),
-                    File::B => str.push('b'),
-                    File::C => str.push('c'),
-                    File::D => str.push('d'),
-                    File::E => str.push('e'),
-                    File::F => str.push('f'),
-                    File::G => str.push('g'),
-                    File::H => str.push('h'),
-                    File::Null => str.push('X')
-                };
-                match self.rank() {
-                    Rank::First => str.push('1'),
-                    Rank::Second => str.push('2'),
-                    Rank::Third => str.push('3'),
-                    Rank::Fourth => str.push('4'),
-                    Rank::Fifth => str.push('5'),
-                    Rank::Sixth => str.push('6'),
-                    Rank::Seventh => str.push('7'),
-                    Rank::Eighth => str.push('8'),
-                    Rank::Null => str.push('X')
+                // Widened so that no offset can overflow
+                let f_val = self.file() as isize + dx as isize;
+                let r_val = self.rank() as isize + dy as isize;
+                if !(0..File::COUNT as isize).contains(&f_val)
+                    || !(0..Rank::COUNT as isize).contains(&r_val) {
+                    return Square::Null;
                 }
-                str
+                Square::from(File::convert(f_val), Rank::convert(r_val))
             }
         }
     }
-    
+
     /// "Flips" representation of the square
     /// 
     /// Used when representing the chess board from the opponent's perspective.
@@ -425,11 +496,27 @@ impl Square {
 
     /// Prints the square
     pub fn print(&self) -> () {
-        println!("{}", self.to_string());
+        println!("{}", self);
     }
 }
 
-/// The IntoIterator made from two [Squares](Square)
+/// Converts an index from 0 (a1) to 63 (h8) into a square
+///
+/// Unlike [`Square::new`], which maps larger values to `Null`, this refuses
+/// them.
+impl TryFrom<u8> for Square {
+    type Error = OutOfRange<u8>;
+
+    fn try_from(s: u8) -> Result<Square, Self::Error> {
+        match s {
+            Square::MIN_VAL..=Square::MAX_VAL => Ok(Square::Sq(s)),
+            _ => Err(OutOfRange(s)),
+        }
+    }
+}
+
+/// The IntoIterator made from two [Squares](Square) (see
+/// [`Square::range_from_int`])
 #[derive(Copy, Clone, Debug)]
 pub struct SquareRange {
     current: u8,
@@ -440,17 +527,33 @@ impl Iterator for SquareRange {
     type Item = Square;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current == self.end {
-            None
-        } else {
-            let result = Square::Sq(self.current);
-            if self.current < self.end { self.current += 1 }
-            else { self.current -= 1 } // self.current > self.end
-            Some(result)
+        match self.current.cmp(&self.end) {
+            Ordering::Equal => None,
+            Ordering::Less => {
+                self.current += 1;
+                Some(Square::Sq(self.current - 1))
+            }
+            // Going down, the upper bound `current` is excluded
+            Ordering::Greater => {
+                self.current -= 1;
+                Some(Square::Sq(self.current))
+            }
         }
     }
 }
 
+/// Helper constant, the bits of the a-file
+const FILE_A_BITS: u64 = 0x0101010101010101;
+
+/// Helper constant, the bits of the first rank
+const RANK_1_BITS: u64 = 0xff;
+
+/// Helper constant, every bit but the a-file's
+const NOT_A_FILE: u64 = !FILE_A_BITS;
+
+/// Helper constant, every bit but the h-file's
+const NOT_H_FILE: u64 = !(FILE_A_BITS << 7);
+
 /// A 64-bit value, where each bit represents the occupancy of a square
 /// 
 /// The least-significant bit represents `Square(0)` (a1), and the 
@@ -499,6 +602,18 @@ impl ops::BitXor for Bitboard {
     }
 }
 
+/// Set difference, the same as [`without`](Bitboard::without)
+impl ops::Sub for Bitboard {
+    type Output = Bitboard;
+
+    fn sub(self, rhs: Self) -> Bitboard {
+        match (self, rhs) {
+            (Bitboard::Bb(a), Bitboard::Bb(b)) => Bitboard::Bb(a & !b),
+            (_, _) => panic!("Attempted to - with Bitboard::Null"),
+        }
+    }
+}
+
 impl ops::Not for Bitboard {
     type Output = Bitboard;
 
@@ -535,6 +650,46 @@ impl Bitboard {
 
     /// A bitboard with none of the bits set
     pub const EMPTY: Bitboard = Bitboard::Bb(0u64);
+
+    /// The squares on each file
+    pub const FILE_A: Bitboard = Bitboard::Bb(FILE_A_BITS);
+    pub const FILE_B: Bitboard = Bitboard::Bb(FILE_A_BITS << 1);
+    pub const FILE_C: Bitboard = Bitboard::Bb(FILE_A_BITS << 2);
+    pub const FILE_D: Bitboard = Bitboard::Bb(FILE_A_BITS << 3);
+    pub const FILE_E: Bitboard = Bitboard::Bb(FILE_A_BITS << 4);
+    pub const FILE_F: Bitboard = Bitboard::Bb(FILE_A_BITS << 5);
+    pub const FILE_G: Bitboard = Bitboard::Bb(FILE_A_BITS << 6);
+    pub const FILE_H: Bitboard = Bitboard::Bb(FILE_A_BITS << 7);
+
+    /// The squares on each rank
+    pub const RANK_1: Bitboard = Bitboard::Bb(RANK_1_BITS);
+    pub const RANK_2: Bitboard = Bitboard::Bb(RANK_1_BITS << 8);
+    pub const RANK_3: Bitboard = Bitboard::Bb(RANK_1_BITS << 16);
+    pub const RANK_4: Bitboard = Bitboard::Bb(RANK_1_BITS << 24);
+    pub const RANK_5: Bitboard = Bitboard::Bb(RANK_1_BITS << 32);
+    pub const RANK_6: Bitboard = Bitboard::Bb(RANK_1_BITS << 40);
+    pub const RANK_7: Bitboard = Bitboard::Bb(RANK_1_BITS << 48);
+    pub const RANK_8: Bitboard = Bitboard::Bb(RANK_1_BITS << 56);
+
+    /// Every file, indexed by [`File`]
+    pub const FILES: [Bitboard; 8] = [
+        Bitboard::FILE_A, Bitboard::FILE_B, Bitboard::FILE_C, Bitboard::FILE_D,
+        Bitboard::FILE_E, Bitboard::FILE_F, Bitboard::FILE_G, Bitboard::FILE_H,
+    ];
+
+    /// Every rank, indexed by [`Rank`]
+    pub const RANKS: [Bitboard; 8] = [
+        Bitboard::RANK_1, Bitboard::RANK_2, Bitboard::RANK_3, Bitboard::RANK_4,
+        Bitboard::RANK_5, Bitboard::RANK_6, Bitboard::RANK_7, Bitboard::RANK_8,
+    ];
+
+    /// The squares on the edge of the board (the a- and h-files along with
+    /// the first and eighth ranks)
+    pub const EDGES: Bitboard = Bitboard::Bb(
+        FILE_A_BITS | (FILE_A_BITS << 7) | RANK_1_BITS | (RANK_1_BITS << 56));
+
+    /// The four center squares (d4, e4, d5, and e5)
+    pub const CENTER: Bitboard = Bitboard::Bb(0x0000001818000000);
     
     /// Creates a new bitboard
     pub fn new(b: u64) -> Bitboard {
@@ -549,12 +704,67 @@ impl Bitboard {
         }
     }
 
+    /// Returns the squares set in the bitboard but not in `other` (also
+    /// written `self - other`)
+    pub fn without(&self, other: Bitboard) -> Bitboard {
+        *self - other
+    }
+
     /// Returns whether or not the `s`th bit is set
     pub fn get(&self, sq: Square) -> bool {
         match (*self, sq) {
             (Bitboard::Null, _) => panic!("Attempted to get on Bitboard::Null"),
             (_, Square::Null) => panic!("Attempted to get with Bitboard::Null"),
-            (B, s) => !((B & sq.to_bitboard()).is_empty()),
+            (b, _) => !((b & sq.to_bitboard()).is_empty()),
+        }
+    }
+
+    /// Returns whether or not `sq` is set (the same as
+    /// [`get`](Bitboard::get))
+    pub fn contains(&self, sq: Square) -> bool {
+        self.get(sq)
+    }
+
+    /// Returns whether or not the bitboard shares a set square with `other`
+    pub fn intersects(&self, other: Bitboard) -> bool {
+        !(*self & other).is_empty()
+    }
+
+    /// Returns whether or not the bitboard shares no set square with `other`
+    pub fn is_disjoint(&self, other: Bitboard) -> bool {
+        (*self & other).is_empty()
+    }
+
+    /// Like [`get`](Bitboard::get), but returns `None` if either the
+    /// bitboard or the square is `Null`
+    pub fn try_get(&self, sq: Square) -> Option<bool> {
+        match (self, sq) {
+            (Bitboard::Null, _) | (_, Square::Null) => None,
+            _ => Some(self.get(sq)),
+        }
+    }
+
+    /// Like [`set`](Bitboard::set), but returns `None` (leaving the bitboard
+    /// unchanged) if either the bitboard or the square is `Null`
+    pub fn try_set(&mut self, sq: Square) -> Option<()> {
+        match (&self, sq) {
+            (Bitboard::Null, _) | (_, Square::Null) => None,
+            _ => {
+                self.set(sq);
+                Some(())
+            }
+        }
+    }
+
+    /// Like [`reset`](Bitboard::reset), but returns `None` (leaving the
+    /// bitboard unchanged) if either the bitboard or the square is `Null`
+    pub fn try_reset(&mut self, sq: Square) -> Option<()> {
+        match (&self, sq) {
+            (Bitboard::Null, _) | (_, Square::Null) => None,
+            _ => {
+                self.reset(sq);
+                Some(())
+            }
         }
     }
 
@@ -607,6 +817,22 @@ impl Bitboard {
         }
     }
 
+    /// Returns the number of set bits on file `f`
+    pub fn count_on_file(&self, f: File) -> u8 {
+        match f {
+            File::Null => panic!("Attempted to count on File::Null"),
+            _ => (*self & Bitboard::FILES[f as usize]).pop_count(),
+        }
+    }
+
+    /// Returns the number of set bits on rank `r`
+    pub fn count_on_rank(&self, r: Rank) -> u8 {
+        match r {
+            Rank::Null => panic!("Attempted to count on Rank::Null"),
+            _ => (*self & Bitboard::RANKS[r as usize]).pop_count(),
+        }
+    }
+
     /// Returns the least-significant set bit as a [`Square`]
     pub fn lsb(&self) -> Square {
         match self {
@@ -616,6 +842,21 @@ impl Bitboard {
         }
     }
 
+    /// Removes the least-significant set bit and returns it as a [`Square`]
+    /// (`None` if the bitboard is empty)
+    /// 
+    /// Unlike iterating, this leaves the rest of the bitboard in place
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        match *self {
+            Bitboard::Null => panic!("Attempted to pop LSB of Bitboard::Null"),
+            Bitboard::Bb(0u64) => None,
+            Bitboard::Bb(b) => {
+                *self = Bitboard::Bb(b & (b - 1));
+                Some(Square::Sq(b.trailing_zeros() as u8))
+            }
+        }
+    }
+
     /// Returns the most-significant set bit as a [`Square`]
     pub fn msb(&self) -> Square {
         match self {
@@ -635,35 +876,224 @@ impl Bitboard {
         }
     }
 
+    /// Returns the bitboard mirrored left to right (the a-file swaps with the
+    /// h-file, and so on), keeping every bit on its rank
+    /// 
+    /// Unlike [`flip`](Bitboard::flip), which rotates the board by 180
+    /// degrees, this is a pure reflection
+    pub fn mirror_horizontal(&self) -> Bitboard {
+        const K1: u64 = 0x5555555555555555;
+        const K2: u64 = 0x3333333333333333;
+        const K4: u64 = 0x0f0f0f0f0f0f0f0f;
+        match *self {
+            Bitboard::Null => panic!("Attempted to mirror Bitboard::Null"),
+            Bitboard::Bb(b) => {
+                // Swap neighbouring files, then pairs of files, then nibbles
+                let b = ((b >> 1) & K1) | ((b & K1) << 1);
+                let b = ((b >> 2) & K2) | ((b & K2) << 2);
+                let b = ((b >> 4) & K4) | ((b & K4) << 4);
+                Bitboard::Bb(b)
+            }
+        }
+    }
+
+    /// Returns the bitboard mirrored top to bottom (the first rank swaps with
+    /// the eighth, and so on), keeping every bit on its file
+    pub fn mirror_vertical(&self) -> Bitboard {
+        match *self {
+            Bitboard::Null => panic!("Attempted to mirror Bitboard::Null"),
+            // Each rank is one byte
+            Bitboard::Bb(b) => Bitboard::Bb(b.swap_bytes()),
+        }
+    }
+
+    /// Returns the bitboard rotated 90 degrees clockwise (as seen from White's
+    /// side, so a1 goes to a8 and h1 to a1)
+    pub fn rotate_90_cw(&self) -> Bitboard {
+        match *self {
+            Bitboard::Null => panic!("Attempted to rotate Bitboard::Null"),
+            Bitboard::Bb(b) => Bitboard::Bb(flip_diagonal(b).swap_bytes()),
+        }
+    }
+
+    /// Returns the bitboard rotated 90 degrees counterclockwise (as seen from
+    /// White's side, so a1 goes to h1 and a8 to a1)
+    pub fn rotate_90_ccw(&self) -> Bitboard {
+        match *self {
+            Bitboard::Null => panic!("Attempted to rotate Bitboard::Null"),
+            Bitboard::Bb(b) => Bitboard::Bb(flip_diagonal(b.swap_bytes())),
+        }
+    }
+
+    /// Returns the bitboard along with every square a king's move away from
+    /// one of its set bits
+    pub fn expand(&self) -> Bitboard {
+        // Masks out the bits that would wrap around to the other side
+        match self {
+            Bitboard::Null => panic!("Attempted to expand Bitboard::Null"),
+            Bitboard::Bb(b) => {
+                let row = *b | ((*b << 1) & NOT_A_FILE) | ((*b >> 1) & NOT_H_FILE);
+                Bitboard::Bb(row | (row << 8) | (row >> 8))
+            }
+        }
+    }
+
+    /// Returns the bitboard with every set bit moved one square in `dir`
+    /// 
+    /// Bits moved off the edge of the board are dropped rather than wrapping
+    /// around onto the other side
+    pub fn shift(&self, dir: Direction) -> Bitboard {
+        let (amount, wrap) = shift_params(dir);
+        match *self {
+            Bitboard::Null => panic!("Attempted to shift Bitboard::Null"),
+            Bitboard::Bb(b) => Bitboard::Bb(shift_raw(b, amount) & wrap),
+        }
+    }
+
+    /// Returns the bitboard along with every square reached by sliding its
+    /// set bits in `dir` through the squares in `empty`
+    /// 
+    /// A slide stops just before the first square not in `empty` (so, as with
+    /// rays, blockers aren't included). Uses a Kogge-Stone parallel prefix
+    /// fill, which slides every bit at once in three shift steps
+    pub fn fill(&self, dir: Direction, empty: Bitboard) -> Bitboard {
+        let (amount, wrap) = shift_params(dir);
+        match (*self, empty) {
+            (Bitboard::Bb(b), Bitboard::Bb(e)) => {
+                let mut gen = b;
+                // Squares the fill may pass through, minus those a shift
+                // would have wrapped onto from the other side of the board
+                let mut pro = e & wrap;
+                gen |= pro & shift_raw(gen, amount);
+                pro &= shift_raw(pro, amount);
+                gen |= pro & shift_raw(gen, 2 * amount);
+                pro &= shift_raw(pro, 2 * amount);
+                gen |= pro & shift_raw(gen, 4 * amount);
+                Bitboard::Bb(gen)
+            }
+            (_, _) => panic!("Attempted to fill with Bitboard::Null"),
+        }
+    }
+
+    /// Returns an iterator over every subset of the set bits, from the empty
+    /// bitboard up to the bitboard itself
+    pub fn subsets(&self) -> SubsetIter {
+        match *self {
+            Bitboard::Null => panic!("Attempted to get subsets of Bitboard::Null"),
+            Bitboard::Bb(mask) => SubsetIter { mask, subset: 0u64, done: false },
+        }
+    }
+
     /// Prints the bitboard as an 8x8 grid
     pub fn print(&self) {
+        println!("{}", self);
+    }
+}
+
+impl fmt::Display for Bitboard {
+    /// Writes the bitboard as an 8x8 grid of `x` (set) and `.` (unset), with
+    /// the eighth rank on top
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Bitboard::Null => println!("Bitboard::Null"),
-            B => {
-                let mut bb_string = String::new();
+            Bitboard::Null => write!(f, "Null"),
+            b => {
                 for row in PRINT_ORDER {
                     for i in row {
-                        if B.get(Square::Sq(*i)) { 
-                            bb_string.push_str("x ");
-                        } 
-                        else {
-                            bb_string.push_str(". ");
+                        if b.get(Square::Sq(*i)) {
+                            write!(f, "x ")?;
+                        } else {
+                            write!(f, ". ")?;
                         }
                     }
-                    bb_string.push('\n');
+                    writeln!(f)?;
                 }
-                println!("{}", bb_string);
+                Ok(())
             }
         }
     }
 }
 
+/// Helper function, reflects `b` about the a1-h8 diagonal (swapping each
+/// square's file and rank)
+fn flip_diagonal(b: u64) -> u64 {
+    const K1: u64 = 0x5500550055005500;
+    const K2: u64 = 0x3333000033330000;
+    const K4: u64 = 0x0f0f0f0f00000000;
+    // Swap 4x4 blocks, then 2x2 blocks within them, then single squares
+    let mut b = b;
+    let t = K4 & (b ^ (b << 28));
+    b ^= t ^ (t >> 28);
+    let t = K2 & (b ^ (b << 14));
+    b ^= t ^ (t >> 14);
+    let t = K1 & (b ^ (b << 7));
+    b ^= t ^ (t >> 7);
+    b
+}
+
+/// Helper function, returns the bit shift moving a square one step in `dir`
+/// and the mask of squares that step can land on without wrapping around
+fn shift_params(dir: Direction) -> (i8, u64) {
+    match dir {
+        Direction::North => (8, !0u64),
+        Direction::East => (1, NOT_A_FILE),
+        Direction::South => (-8, !0u64),
+        Direction::West => (-1, NOT_H_FILE),
+        Direction::Northeast => (9, NOT_A_FILE),
+        Direction::Southeast => (-7, NOT_A_FILE),
+        Direction::Southwest => (-9, NOT_H_FILE),
+        Direction::Northwest => (7, NOT_H_FILE),
+    }
+}
+
+/// Helper function, shifts `b` left by `amount` bits, or right if negative
+fn shift_raw(b: u64, amount: i8) -> u64 {
+    if amount >= 0 {
+        b << amount
+    } else {
+        b >> -amount
+    }
+}
+
+/// Iterator over the subsets of a [`Bitboard`] (see [`Bitboard::subsets`])
+#[derive(Copy, Clone, Debug)]
+pub struct SubsetIter {
+    mask: u64,
+    subset: u64,
+    done: bool,
+}
+
+impl Iterator for SubsetIter {
+    type Item = Bitboard;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = Bitboard::Bb(self.subset);
+        // Carry-rippler: adds one to the subset as if the bits outside the
+        // mask weren't there, wrapping back to zero after the full mask
+        self.subset = self.subset.wrapping_sub(self.mask) & self.mask;
+        self.done = self.subset == 0u64;
+        Some(result)
+    }
+}
+
+impl FromIterator<Square> for Bitboard {
+    fn from_iter<I: IntoIterator<Item = Square>>(iter: I) -> Bitboard {
+        let mut bb = Bitboard::EMPTY;
+        for sq in iter {
+            bb.set(sq);
+        }
+        bb
+    }
+}
+
 #[cfg(test)]
 mod tests {
     
     use super::*;
 
-    // #[test]
+    #[test]
     fn test_square() {
         assert_eq!(Square::Sq(11u8), Square::from(File::D, Rank::Second));
         
@@ -672,7 +1102,7 @@ mod tests {
         assert_eq!(Square::Sq(48u8), s1);
     }
 
-    // #[test]
+    #[test]
     fn test_bitboard() {
         let mut b1 = Bitboard::EMPTY;
         let s1: Square = Square::Sq(10u8);
@@ -702,13 +1132,296 @@ mod tests {
         assert!(b1.is_empty());
     }
 
-    // #[test]
+    #[test]
+    fn test_try_from() {
+        assert_eq!(Square::try_from(0u8), Ok(Square::Sq(0)));
+        assert_eq!(Square::try_from(63u8), Ok(Square::Sq(63)));
+        assert_eq!(Square::try_from(64u8), Err(OutOfRange(64)));
+        assert_eq!(Square::try_from(255u8), Err(OutOfRange(255)));
+        assert_eq!(File::try_from('a'), Ok(File::A));
+        assert_eq!(File::try_from('h'), Ok(File::H));
+        assert_eq!(File::try_from('i'), Err(OutOfRange('i')));
+        assert_eq!(File::try_from('A'), Err(OutOfRange('A')));
+        assert_eq!(Rank::try_from('1'), Ok(Rank::First));
+        assert_eq!(Rank::try_from('8'), Ok(Rank::Eighth));
+        assert_eq!(Rank::try_from('0'), Err(OutOfRange('0')));
+        assert_eq!(Rank::try_from('9'), Err(OutOfRange('9')));
+        assert_eq!(OutOfRange('9').to_string(), "'9' is out of range");
+    }
+
+    #[test]
+    fn test_try_accessors() {
+        let e4 = Square::from(File::E, Rank::Fourth);
+        assert_eq!(Square::Null.try_val(), None);
+        assert_eq!(e4.try_val(), Some(28));
+        assert_eq!(Square::Null.try_rank(), None);
+        assert_eq!(Square::Null.try_file(), None);
+        assert_eq!(e4.try_rank(), Some(Rank::Fourth));
+        assert_eq!(e4.try_file(), Some(File::E));
+        let mut b = Bitboard::EMPTY;
+        assert_eq!(b.try_set(Square::Null), None);
+        assert_eq!(b.try_set(e4), Some(()));
+        assert_eq!(b.try_get(e4), Some(true));
+        assert_eq!(b.try_get(Square::Null), None);
+        assert_eq!(b.try_reset(e4), Some(()));
+        assert_eq!(b, Bitboard::EMPTY);
+        let mut null = Bitboard::Null;
+        assert_eq!(null.try_get(e4), None);
+        assert_eq!(null.try_set(e4), None);
+        assert_eq!(null.try_reset(e4), None);
+        assert_eq!(null, Bitboard::Null);
+    }
+
+    #[test]
+    fn test_pop_lsb() {
+        let b1 = Square::from(File::B, Rank::First);
+        let g7 = Square::from(File::G, Rank::Seventh);
+        let mut bb = b1.to_bitboard() | g7.to_bitboard();
+        assert_eq!(bb.pop_lsb(), Some(b1));
+        assert_eq!(bb, g7.to_bitboard());
+        assert_eq!(bb.pop_lsb(), Some(g7));
+        assert_eq!(bb, Bitboard::EMPTY);
+        assert_eq!(bb.pop_lsb(), None);
+    }
+
+    #[test]
+    fn test_mirror() {
+        let b1 = Square::from(File::B, Rank::First).to_bitboard();
+        assert_eq!(b1.mirror_horizontal(), Square::from(File::G, Rank::First).to_bitboard());
+        assert_eq!(b1.mirror_vertical(), Square::from(File::B, Rank::Eighth).to_bitboard());
+        // Both mirrors together are the 180 degree flip
+        let bb = Bitboard::new(0x0123456789abcdef);
+        let mut flipped = bb;
+        flipped.flip();
+        assert_eq!(bb.mirror_horizontal().mirror_vertical(), flipped);
+        assert_eq!(bb.mirror_horizontal().mirror_horizontal(), bb);
+        assert_eq!(bb.mirror_vertical().mirror_vertical(), bb);
+    }
+
+    #[test]
+    fn test_display_from_iter() {
+        let squares = [Square::from(File::A, Rank::First), Square::from(File::H, Rank::Eighth)];
+        let bb: Bitboard = squares.into_iter().collect();
+        assert_eq!(bb, Bitboard::new(0x8000000000000001));
+        assert_eq!(format!("{}", bb),
+                   ". . . . . . . x \n\
+                    . . . . . . . . \n\
+                    . . . . . . . . \n\
+                    . . . . . . . . \n\
+                    . . . . . . . . \n\
+                    . . . . . . . . \n\
+                    . . . . . . . . \n\
+                    x . . . . . . . \n");
+        assert_eq!(format!("{}", Bitboard::Null), "Null");
+        assert_eq!(bb.into_iter().collect::<Bitboard>(), bb);
+    }
+
+    #[test]
+    fn test_subsets() {
+        let mask = Bitboard::new(0b1000_0101 << 16);
+        let subsets: Vec<Bitboard> = mask.subsets().collect();
+        assert_eq!(subsets.len(), 8);
+        assert_eq!(subsets[0], Bitboard::EMPTY);
+        assert_eq!(subsets[7], mask);
+        for (i, sub) in subsets.iter().enumerate() {
+            assert_eq!(*sub & !mask, Bitboard::EMPTY);
+            assert!(!subsets[..i].contains(sub));
+        }
+        assert_eq!(Bitboard::EMPTY.subsets().collect::<Vec<_>>(), vec![Bitboard::EMPTY]);
+    }
+
+    #[test]
+    fn test_expand() {
+        let e4 = Square::from(File::E, Rank::Fourth).to_bitboard();
+        let expanded = e4.expand();
+        assert_eq!(expanded.pop_count(), 9);
+        for (dx, dy) in [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 0),
+                         (0, 1), (1, -1), (1, 0), (1, 1)] {
+            assert!(expanded.get(Square::from(File::E, Rank::Fourth).offset(dx, dy)));
+        }
+        let a1 = Square::from(File::A, Rank::First).to_bitboard();
+        assert_eq!(a1.expand().pop_count(), 4);
+        let h4 = Square::from(File::H, Rank::Fourth).to_bitboard();
+        assert_eq!(h4.expand().pop_count(), 6);
+        assert!(!h4.expand().get(Square::from(File::A, Rank::Fifth)));
+        assert_eq!(Bitboard::EMPTY.expand(), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_square_all() {
+        let squares: Vec<Square> = Square::all().collect();
+        assert_eq!(squares.len(), Square::COUNT);
+        assert_eq!(squares[0], Square::from(File::A, Rank::First));
+        assert_eq!(squares[63], Square::from(File::H, Rank::Eighth));
+    }
+
+    #[test]
+    fn test_square_range() {
+        let squares: Vec<Square> = Square::range_from_int(0, 64).collect();
+        assert_eq!(squares, Square::all().collect::<Vec<_>>());
+        let squares: Vec<Square> = Square::range_from_int(64, 0).collect();
+        assert_eq!(squares.len(), 64);
+        assert_eq!(squares[0], Square::from(File::H, Rank::Eighth));
+        assert_eq!(squares[63], Square::from(File::A, Rank::First));
+        // Descending ranges cover the same squares as ascending ones
+        let up: Vec<Square> = Square::range_from_int(8, 16).collect();
+        let mut down: Vec<Square> = Square::range_from_int(16, 8).collect();
+        down.reverse();
+        assert_eq!(up, down);
+        assert_eq!(up.last(), Some(&Square::from(File::H, Rank::Second)));
+        // Single-element and empty ranges
+        let e4 = Square::from(File::E, Rank::Fourth);
+        let f4 = Square::from(File::F, Rank::Fourth);
+        assert_eq!(Square::range(e4, f4).collect::<Vec<_>>(), vec![e4]);
+        assert_eq!(Square::range(f4, e4).collect::<Vec<_>>(), vec![e4]);
+        assert_eq!(Square::range(e4, e4).count(), 0);
+        assert_eq!(Square::range_from_int(64, 63).collect::<Vec<_>>(),
+                   vec![Square::from(File::H, Rank::Eighth)]);
+    }
+
+    #[test]
     fn test_offset() {
         assert_eq!(Square::from(File::E, Rank::Fourth).offset(2, 1),
                    Square::from(File::G, Rank::Fifth));
         assert_eq!(Square::from(File::E, Rank::Fourth).offset(5, 1),
                    Square::Null);
+        // Off the board in every direction from each corner
+        let a1 = Square::from(File::A, Rank::First);
+        let h1 = Square::from(File::H, Rank::First);
+        let a8 = Square::from(File::A, Rank::Eighth);
+        let h8 = Square::from(File::H, Rank::Eighth);
+        for (corner, dx, dy) in [(a1, -1, 0), (a1, 0, -1), (a1, -3, 0), (a1, -1, -1),
+                                 (h1, 1, 0), (h1, 0, -1), (h1, 3, 2), (h1, 1, -1),
+                                 (a8, -1, 0), (a8, 0, 1), (a8, 0, 3), (a8, -1, 1),
+                                 (h8, 1, 0), (h8, 0, 1), (h8, -2, 1), (h8, 1, 1)] {
+            assert_eq!(corner.offset(dx, dy), Square::Null, "{} {} {}", corner, dx, dy);
+        }
+        // Moving a file left from the a-file must not wrap onto the h-file
+        assert_eq!(Square::from(File::A, Rank::Fourth).offset(-1, 1), Square::Null);
+        assert_eq!(a1.offset(7, 7), h8);
+        assert_eq!(h8.offset(-7, -7), a1);
+        assert_eq!(a1.offset(i8::MIN, 0), Square::Null);
+        assert_eq!(h8.offset(i8::MAX, i8::MAX), Square::Null);
+    }
+
+    #[test]
+    fn test_count_on_file_rank() {
+        // Doubled pawns on d2 and d4, and another on e4
+        let mut b = Bitboard::EMPTY;
+        for (f, r) in [(File::D, Rank::Second), (File::D, Rank::Fourth), (File::E, Rank::Fourth)] {
+            b.set(Square::from(f, r));
+        }
+        assert_eq!(b.count_on_file(File::D), 2);
+        assert_eq!(b.count_on_file(File::E), 1);
+        assert_eq!(b.count_on_file(File::A), 0);
+        assert_eq!(b.count_on_rank(Rank::Fourth), 2);
+        assert_eq!(b.count_on_rank(Rank::Second), 1);
+        assert_eq!(Bitboard::EMPTY.count_on_rank(Rank::Eighth), 0);
+    }
+
+    #[test]
+    fn test_fill() {
+        let a1 = Square::from(File::A, Rank::First);
+        let rook = a1.to_bitboard();
+        let a_file: Bitboard = (0..8).map(|r| Square::from(File::A, Rank::convert(r))).collect();
+        assert_eq!(rook.fill(Direction::North, !rook), a_file);
+        // Nothing to slide through to the south or west
+        assert_eq!(rook.fill(Direction::South, !rook), rook);
+        assert_eq!(rook.fill(Direction::West, !rook), rook);
+        // The slide stops before a blocker on a4, and never wraps onto the
+        // next rank
+        let a4 = Square::from(File::A, Rank::Fourth);
+        let empty = !(rook | a4.to_bitboard());
+        assert_eq!(rook.fill(Direction::North, empty).pop_count(), 3);
+        assert_eq!(rook.fill(Direction::East, Bitboard::FULL).pop_count(), 8);
+        assert_eq!(rook.fill(Direction::Northeast, Bitboard::FULL).pop_count(), 8);
+        let h1 = Square::from(File::H, Rank::First).to_bitboard();
+        assert_eq!(h1.fill(Direction::East, Bitboard::FULL), h1);
+        assert_eq!(h1.fill(Direction::Northwest, Bitboard::FULL).pop_count(), 8);
+    }
 
+    #[test]
+    fn test_shift() {
+        let d4 = Square::from(File::D, Rank::Fourth);
+        for (dir, dx, dy) in [(Direction::North, 0, 1), (Direction::East, 1, 0),
+                              (Direction::South, 0, -1), (Direction::West, -1, 0),
+                              (Direction::Northeast, 1, 1), (Direction::Southeast, 1, -1),
+                              (Direction::Southwest, -1, -1), (Direction::Northwest, -1, 1)] {
+            assert_eq!(d4.to_bitboard().shift(dir), d4.offset(dx, dy).to_bitboard(), "{:?}", dir);
+        }
+        // Off the edge of the board
+        let h4 = Square::from(File::H, Rank::Fourth).to_bitboard();
+        assert_eq!(h4.shift(Direction::East), Bitboard::EMPTY);
+        assert_eq!(h4.shift(Direction::Southeast), Bitboard::EMPTY);
+        let a8 = Square::from(File::A, Rank::Eighth).to_bitboard();
+        assert_eq!(a8.shift(Direction::North), Bitboard::EMPTY);
+        assert_eq!(a8.shift(Direction::West), Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_masks() {
+        assert_eq!(Bitboard::FILE_A.pop_count(), 8);
+        assert_eq!(Bitboard::RANK_8.pop_count(), 8);
+        assert!(Bitboard::FILE_A.get(Square::from(File::A, Rank::Eighth)));
+        assert!(Bitboard::FILE_H.get(Square::from(File::H, Rank::First)));
+        assert!(Bitboard::RANK_1.get(Square::from(File::H, Rank::First)));
+        assert!(Bitboard::RANK_8.get(Square::from(File::A, Rank::Eighth)));
+        for i in 0..8 {
+            let (f, r) = (File::convert(i), Rank::convert(i));
+            assert_eq!(Bitboard::FILES[i as usize],
+                       (0..8).map(|j| Square::from(f, Rank::convert(j))).collect());
+            assert_eq!(Bitboard::RANKS[i as usize],
+                       (0..8).map(|j| Square::from(File::convert(j), r)).collect());
+        }
+        assert_eq!(Bitboard::EDGES.pop_count(), 28);
+        assert_eq!(Bitboard::CENTER,
+                   [(File::D, Rank::Fourth), (File::E, Rank::Fourth),
+                    (File::D, Rank::Fifth), (File::E, Rank::Fifth)]
+                   .into_iter().map(|(f, r)| Square::from(f, r)).collect());
     }
 
+    #[test]
+    fn test_rotate_90() {
+        let sq = |f, r| Square::from(f, r).to_bitboard();
+        assert_eq!(sq(File::A, Rank::First).rotate_90_cw(), sq(File::A, Rank::Eighth));
+        assert_eq!(sq(File::H, Rank::First).rotate_90_cw(), sq(File::A, Rank::First));
+        assert_eq!(sq(File::A, Rank::First).rotate_90_ccw(), sq(File::H, Rank::First));
+        assert_eq!(sq(File::B, Rank::First).rotate_90_ccw(), sq(File::H, Rank::Second));
+        assert_eq!(Bitboard::RANK_1.rotate_90_cw(), Bitboard::FILE_A);
+        assert_eq!(Bitboard::FILE_A.rotate_90_ccw(), Bitboard::RANK_1);
+        // Twice is the same as the 180 degree flip
+        let mut b = Bitboard::new(0x0000_1234_0081_f00d);
+        let mut flipped = b;
+        flipped.flip();
+        assert_eq!(b.rotate_90_cw().rotate_90_cw(), flipped);
+        assert_eq!(b.rotate_90_cw().rotate_90_ccw(), b);
+        for _ in 0..4 {
+            b = b.rotate_90_cw();
+        }
+        assert_eq!(b, Bitboard::new(0x0000_1234_0081_f00d));
+    }
+
+    #[test]
+    fn test_without() {
+        assert_eq!(Bitboard::FULL.without(Bitboard::FILE_A).pop_count(), 56);
+        assert_eq!(Bitboard::FULL - Bitboard::FILE_A, Bitboard::FULL.without(Bitboard::FILE_A));
+        assert_eq!(Bitboard::FILE_A - Bitboard::RANK_1, Bitboard::FILE_A & !Bitboard::RANK_1);
+        assert_eq!(Bitboard::RANK_1.without(Bitboard::RANK_1), Bitboard::EMPTY);
+        assert_eq!(Bitboard::EMPTY - Bitboard::FULL, Bitboard::EMPTY);
+    }
+
+    #[test]
+    fn test_intersects_disjoint() {
+        let a1 = Square::from(File::A, Rank::First);
+        assert!(Bitboard::FILE_A.contains(a1));
+        assert!(!Bitboard::FILE_H.contains(a1));
+        // Overlapping on a1
+        assert!(Bitboard::FILE_A.intersects(Bitboard::RANK_1));
+        assert!(!Bitboard::FILE_A.is_disjoint(Bitboard::RANK_1));
+        // No overlap
+        assert!(!Bitboard::FILE_A.intersects(Bitboard::FILE_B));
+        assert!(Bitboard::FILE_A.is_disjoint(Bitboard::FILE_B));
+        assert!(Bitboard::EMPTY.is_disjoint(Bitboard::FULL));
+        assert!(!Bitboard::EMPTY.intersects(Bitboard::EMPTY));
+    }
 }
@@ -0,0 +1,170 @@
+//! Setting up a position piece by piece
+
+use super::*;
+
+/// Builds a [`Board`] one piece at a time, for setting up positions in code
+///
+/// Squares and colors are absolute (i.e. from White's point of view), as in
+/// FEN, no matter which side is to move. That's why pieces are given as a
+/// [`Color`] and a [`PieceType`] rather than as a [`Piece`], which is only
+/// ours or theirs relative to the side to move. White is to move unless
+/// [`side_to_move`](BoardBuilder::side_to_move) says otherwise, and no side
+/// may castle unless given the right with
+/// [`castling`](BoardBuilder::castling).
+#[derive(Clone, Copy, Debug)]
+pub struct BoardBuilder {
+    pieces: [Option<(Color, PieceType)>; Square::COUNT],
+    color: Color,
+    castling: [[bool; 2]; 2],
+    en_passant: Square,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        BoardBuilder::new()
+    }
+}
+
+impl BoardBuilder {
+    /// Creates a builder for an empty board with White to move
+    pub fn new() -> BoardBuilder {
+        BoardBuilder {
+            pieces: [None; Square::COUNT],
+            color: Color::White,
+            castling: [[false; 2]; 2],
+            en_passant: Square::Null,
+        }
+    }
+
+    /// Puts a `c` piece of type `pt` on `sq`, replacing whatever was there
+    pub fn piece(mut self, sq: Square, c: Color, pt: PieceType) -> BoardBuilder {
+        if sq.is_null() {
+            panic!("Attempted to put a piece on Square::Null");
+        }
+        self.pieces[sq.val() as usize] = Some((c, pt));
+        self
+    }
+
+    /// Sets the color of the side to move
+    pub fn side_to_move(mut self, c: Color) -> BoardBuilder {
+        self.color = c;
+        self
+    }
+
+    /// Gives `c` the right to castle on side `cs`
+    pub fn castling(mut self, c: Color, cs: Side) -> BoardBuilder {
+        self.castling[c as usize][cs as usize] = true;
+        self
+    }
+
+    /// Sets the square a pawn can be captured on en passant (the square the
+    /// pawn skipped over)
+    pub fn en_passant(mut self, sq: Square) -> BoardBuilder {
+        self.en_passant = sq;
+        self
+    }
+
+    /// Builds the board, checking that the position could occur in a game
+    /// (see [`Board::validate`])
+    ///
+    /// The lookup tables must have been built (see
+    /// [`tables::init`](crate::helper::tables::init)).
+    pub fn build(&self) -> Result<Board, IllegalPosition> {
+        let mut board = Board::new();
+        board.board_from_fen(&self.fen());
+        board.validate()?;
+        Ok(board)
+    }
+
+    /// Helper function, writes the position as FEN (with fresh move counters)
+    fn fen(&self) -> String {
+        let mut placement = String::new();
+        for r in (0..Rank::COUNT).rev() {
+            let mut empty = 0;
+            for f in 0..File::COUNT {
+                let sq = Square::from(File::convert(f as isize), Rank::convert(r as isize));
+                match self.pieces[sq.val() as usize] {
+                    None => empty += 1,
+                    Some((c, pt)) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        let w = match c {
+                            Color::White => Whose::Ours,
+                            Color::Black => Whose::Theirs,
+                        };
+                        placement.push(Piece::Pc(w, pt).to_char());
+                    }
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if r > 0 {
+                placement.push('/');
+            }
+        }
+        let mut castling = String::new();
+        for (c, cs, ch) in [(Color::White, Side::K, 'K'), (Color::White, Side::Q, 'Q'),
+                            (Color::Black, Side::K, 'k'), (Color::Black, Side::Q, 'q')] {
+            if self.castling[c as usize][cs as usize] {
+                castling.push(ch);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let color = match self.color {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        let en_passant = match self.en_passant {
+            Square::Null => "-".to_string(),
+            sq => sq.to_string(),
+        };
+        format!("{} {} {} {} 0 1", placement, color, castling, en_passant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        crate::helper::tables::init();
+        let back_rank = [PieceType::R, PieceType::N, PieceType::B, PieceType::Q,
+                         PieceType::K, PieceType::B, PieceType::N, PieceType::R];
+        let mut builder = BoardBuilder::new();
+        for (f, pt) in back_rank.into_iter().enumerate() {
+            let f = File::convert(f as isize);
+            builder = builder
+                .piece(Square::from(f, Rank::First), Color::White, pt)
+                .piece(Square::from(f, Rank::Second), Color::White, PieceType::P)
+                .piece(Square::from(f, Rank::Seventh), Color::Black, PieceType::P)
+                .piece(Square::from(f, Rank::Eighth), Color::Black, pt);
+        }
+        for c in [Color::White, Color::Black] {
+            builder = builder.castling(c, Side::K).castling(c, Side::Q);
+        }
+        assert_eq!(builder.build(), Ok(Board::default()));
+        // White has just played e2e4, so Black can take en passant
+        let square = |f, r| Square::from(f, r);
+        let bd = BoardBuilder::new()
+            .piece(square(File::E, Rank::First), Color::White, PieceType::K)
+            .piece(square(File::E, Rank::Fourth), Color::White, PieceType::P)
+            .piece(square(File::D, Rank::Fourth), Color::Black, PieceType::P)
+            .piece(square(File::E, Rank::Eighth), Color::Black, PieceType::K)
+            .side_to_move(Color::Black)
+            .en_passant(square(File::E, Rank::Third))
+            .build()
+            .unwrap();
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+        // Positions are validated
+        let a1 = Square::from(File::A, Rank::First);
+        assert_eq!(BoardBuilder::new().piece(a1, Color::White, PieceType::K).build(),
+                   Err(IllegalPosition::KingCount(Color::Black, 0)));
+    }
+}
@@ -1,30 +1,55 @@
 //! Keeps track of castling rights
+//!
+//! Along with the rights, the file each castling rook starts on is kept, so
+//! that Chess960 (Fischer Random) positions, where the rooks can start on
+//! any file, castle with the right rook.
 
 use super::*;
 
-/// A 4-bit word representing who still has castling rights
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Castling(u8);
+/// Files of the castling rooks in standard chess, indexed by [`Side`]
+const STANDARD_ROOKS: [File; 2] = [File::H, File::A];
+
+/// A 4-bit word representing who still has castling rights, along with the
+/// (absolute) file of each castling rook
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Castling(u8, [[File; 2]; Whose::COUNT]);
 
 impl Default for Castling {
-    fn default() -> Self { Castling(0u8) }
+    fn default() -> Castling {
+        Castling::EMPTY
+    }
 }
 
 impl Castling {
     /// Both sides can still castle either way
-    pub const FULL: Castling = Castling(0b1111u8);
+    pub const FULL: Castling = Castling(0b1111u8, [STANDARD_ROOKS; Whose::COUNT]);
 
     /// Both sides cannot castle either way
-    pub const EMPTY: Castling = Castling(0b0000u8);
+    pub const EMPTY: Castling = Castling(0b0000u8, [STANDARD_ROOKS; Whose::COUNT]);
 
-    /// Creates a new `Castling`
+    /// Creates a new `Castling` with the rooks on their standard files
     pub fn new(v: u8) -> Castling {
-        Castling(v)
+        Castling(v, [STANDARD_ROOKS; Whose::COUNT])
+    }
+
+    /// Returns the file the rook of `w` castling to side `cs` starts on
+    pub fn rook_file(&self, w: Whose, cs: Side) -> File {
+        self.1[w as usize][cs as usize]
+    }
+
+    /// Sets the file the rook of `w` castling to side `cs` starts on
+    pub fn set_rook_file(&mut self, w: Whose, cs: Side, f: File) -> () {
+        self.1[w as usize][cs as usize] = f;
+    }
+
+    /// Returns whether or not every castling rook is on its standard file
+    pub fn is_standard(&self) -> bool {
+        self.1 == [STANDARD_ROOKS; Whose::COUNT]
     }
 
     /// Gets the castling rights for a side and player
-    pub fn get(&mut self, w: Whose, cs: Side) -> bool {
-        let Castling(v) = *self;
+    pub fn get(&self, w: Whose, cs: Side) -> bool {
+        let Castling(v, _) = *self;
         match (w, cs) {
             (Whose::Ours, Side::K) => {
                 (v & 0b1000u8) == 0b1000u8
@@ -43,69 +68,175 @@ impl Castling {
 
     /// Sets the castling rights for a side and player
     pub fn set(&mut self, w: Whose, cs: Side) -> () {
-        let Castling(v) = *self;
+        let Castling(v, _) = *self;
         match (w, cs) {
             (Whose::Ours, Side::K) => {
-                *self = Castling(v | 0b1000u8);
+                self.0 = v | 0b1000u8;
             }
             (Whose::Ours, Side::Q) => {
-                *self = Castling(v | 0b0100u8);
+                self.0 = v | 0b0100u8;
             }
             (Whose::Theirs, Side::K) => {
-                *self = Castling(v | 0b0010u8);
+                self.0 = v | 0b0010u8;
             }
             (Whose::Theirs, Side::Q) => {
-                *self = Castling(v | 0b0001u8);
+                self.0 = v | 0b0001u8;
             }
         }
     }
 
     /// Resets the castling rights for a side and player
+    ///
+    /// The rook's file no longer matters, so it goes back to the standard one
     pub fn reset(&mut self, w: Whose, cs: Side) -> () {
-        let Castling(v) = *self;
+        let Castling(v, _) = *self;
+        self.set_rook_file(w, cs, STANDARD_ROOKS[cs as usize]);
         match (w, cs) {
             (Whose::Ours, Side::K) => {
-                *self = Castling(v & 0b0111u8);
+                self.0 = v & 0b0111u8;
             }
             (Whose::Ours, Side::Q) => {
-                *self = Castling(v & 0b1011u8);
+                self.0 = v & 0b1011u8;
             }
             (Whose::Theirs, Side::K) => {
-                *self = Castling(v & 0b1101u8);
+                self.0 = v & 0b1101u8;
             }
             (Whose::Theirs, Side::Q) => {
-                *self = Castling(v & 0b1110u8);
+                self.0 = v & 0b1110u8;
+            }
+        }
+    }
+
+    /// Returns an iterator over the rights that are set, ours first and
+    /// kingside before queenside
+    pub fn iter(&self) -> impl Iterator<Item = (Whose, Side)> {
+        let castling = *self;
+        [(Whose::Ours, Side::K), (Whose::Ours, Side::Q),
+         (Whose::Theirs, Side::K), (Whose::Theirs, Side::Q)]
+            .into_iter()
+            .filter(move |&(w, cs)| castling.get(w, cs))
+    }
+
+    /// Parses the castling field of a FEN string (e.g. `KQkq` or `-`)
+    ///
+    /// Uppercase letters are White's rights and lowercase ones Black's, but
+    /// rights are stored relative to the side to move, so `c` (the color to
+    /// move) decides which of them are ours: with Black to move, `K` is
+    /// their kingside right and `k` ours. The rooks are put on their standard
+    /// files. Panics on any other character.
+    pub fn from_fen_field(s: &str, c: Color) -> Castling {
+        let mut castling = Castling::EMPTY;
+        if s == "-" {
+            return castling;
+        }
+        for ch in s.chars() {
+            let color = if ch.is_ascii_uppercase() { Color::White } else { Color::Black };
+            let cs = match ch.to_ascii_uppercase() {
+                'K' => Side::K,
+                'Q' => Side::Q,
+                _ => panic!("Invalid castling char {}", ch),
+            };
+            castling.set(color.to_whose(c), cs);
+        }
+        castling
+    }
+
+    /// Returns the castling field of a FEN string (e.g. `KQkq`), the inverse
+    /// of [`from_fen_field`](Castling::from_fen_field) for the same `c`
+    ///
+    /// Rights are always listed as `KQkq` (White's first), whoever is to move.
+    pub fn to_fen_field(&self, c: Color) -> String {
+        let mut field = String::new();
+        for (color, cs, ch) in [(Color::White, Side::K, 'K'), (Color::White, Side::Q, 'Q'),
+                                (Color::Black, Side::K, 'k'), (Color::Black, Side::Q, 'q')] {
+            if self.get(color.to_whose(c), cs) {
+                field.push(ch);
             }
         }
+        if field.is_empty() {
+            field.push('-');
+        }
+        field
     }
 
     /// Flips the castling flags such that Our flags become Theirs and Theirs
     /// Ours
     pub fn flip(&mut self) -> () {
-        let Castling(val) = *self;
+        let Castling(val, _) = *self;
         let our_new = (val & 0b1100u8) >> 2;
         let their_new = (val & 0b0011u8) << 2;
-        *self = Castling(our_new | their_new);
+        self.0 = our_new | their_new;
+        self.1.swap(Whose::Ours as usize, Whose::Theirs as usize);
     }
 }
 
-pub enum Side {K , Q}
-
 #[cfg(test)]
 mod tests {
     
     use super::*;
 
-    // #[test]
+    #[test]
     fn test_get_set_reset() {
         let mut c = Castling::new(0u8);
         assert_eq!(c, Castling::default());
         c.set(Whose::Ours, Side::K);
-        assert_eq!(c, Castling(0b1000u8));
-        assert_eq!(c.get(Whose::Ours, Side::K), true);
-        assert_eq!(c.get(Whose::Theirs, Side::Q), false);
+        assert_eq!(c, Castling::new(0b1000u8));
+        assert!(c.get(Whose::Ours, Side::K));
+        assert!(!c.get(Whose::Theirs, Side::Q));
         c.reset(Whose::Ours, Side::K);
-        assert_eq!(c.get(Whose::Ours, Side::K), false);
+        assert!(!c.get(Whose::Ours, Side::K));
+    }
+
+    #[test]
+    fn test_iter() {
+        assert_eq!(Castling::FULL.iter().count(), 4);
+        assert_eq!(Castling::EMPTY.iter().count(), 0);
+        let c = Castling::new(0b1001u8);
+        let rights: Vec<_> = c.iter().collect();
+        assert_eq!(rights, vec![(Whose::Ours, Side::K), (Whose::Theirs, Side::Q)]);
+    }
+
+    #[test]
+    fn test_rook_file() {
+        let mut c = Castling::FULL;
+        assert!(c.is_standard());
+        assert_eq!(c.rook_file(Whose::Theirs, Side::Q), File::A);
+        c.set_rook_file(Whose::Ours, Side::K, File::G);
+        assert!(!c.is_standard());
+        // Flipping hands the rooks over along with the rights
+        c.flip();
+        assert_eq!(c.rook_file(Whose::Theirs, Side::K), File::G);
+        assert_eq!(c.rook_file(Whose::Ours, Side::K), File::H);
+        // Without the right the file no longer matters
+        c.reset(Whose::Theirs, Side::K);
+        assert!(c.is_standard());
+    }
+
+    #[test]
+    fn test_fen_field() {
+        for field in ["KQkq", "Kq", "q", "-"] {
+            for c in [Color::White, Color::Black] {
+                assert_eq!(Castling::from_fen_field(field, c).to_fen_field(c), field);
+            }
+        }
+        assert_eq!(Castling::from_fen_field("KQkq", Color::White), Castling::FULL);
+        assert_eq!(Castling::from_fen_field("-", Color::Black), Castling::EMPTY);
+        // White's rights are ours when White is to move...
+        let c = Castling::from_fen_field("Kq", Color::White);
+        assert!(c.get(Whose::Ours, Side::K));
+        assert!(c.get(Whose::Theirs, Side::Q));
+        assert!(!c.get(Whose::Ours, Side::Q));
+        // ...and theirs when Black is
+        let c = Castling::from_fen_field("Kq", Color::Black);
+        assert!(c.get(Whose::Theirs, Side::K));
+        assert!(c.get(Whose::Ours, Side::Q));
+        assert!(!c.get(Whose::Theirs, Side::Q));
+        // Which is what flipping the board for the next player does
+        let mut flipped = Castling::from_fen_field("Kq", Color::White);
+        flipped.flip();
+        assert_eq!(flipped, c);
+        assert_eq!(c.to_fen_field(Color::Black), "Kq");
+        assert_eq!(c.to_fen_field(Color::White), "Qk");
     }
 
 }
@@ -0,0 +1,136 @@
+//! Extended Position Description (EPD), the format of test suites like WAC
+//!
+//! An EPD line is the first four fields of a FEN string followed by
+//! operations, each an opcode and an operand ended by a semicolon (e.g.
+//! `bm Qg6; id "WAC.001";`).
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::*;
+
+/// Reasons an EPD line can be rejected by [`parse_epd`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EpdError {
+    /// The line does not have all four position fields
+    MissingFields(usize),
+    /// The position is not fit for play
+    Fen(FenError),
+    /// An operation has no opcode, or a quoted operand is never closed
+    BadOperation(String),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EpdError::MissingFields(n) =>
+                write!(f, "EPD has {} position fields, expected 4", n),
+            EpdError::Fen(e) => write!(f, "{}", e),
+            EpdError::BadOperation(op) => write!(f, "malformed EPD operation {:?}", op),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+/// Parses an EPD line into its position and its operations, keyed by opcode
+///
+/// Operands are kept as written, apart from the quotes around strings. The
+/// position starts with both move counters at their initial values.
+pub fn parse_epd(line: &str) -> Result<(Board, HashMap<String, String>), EpdError> {
+    let mut rest = line.trim_start();
+    let mut fields = Vec::new();
+    while fields.len() < 4 && !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        fields.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    if fields.len() != 4 {
+        return Err(EpdError::MissingFields(fields.len()));
+    }
+    let board = Board::from_fen(&format!("{} 0 1", fields.join(" "))).map_err(EpdError::Fen)?;
+    let mut operations = HashMap::new();
+    for op in split_operations(rest)? {
+        let (opcode, operand) = op.split_once(char::is_whitespace).unwrap_or((op, ""));
+        if opcode.is_empty() {
+            return Err(EpdError::BadOperation(String::from(op)));
+        }
+        let operand = operand.trim();
+        let operand = operand.strip_prefix('"')
+            .and_then(|o| o.strip_suffix('"'))
+            .unwrap_or(operand);
+        operations.insert(String::from(opcode), String::from(operand));
+    }
+    Ok((board, operations))
+}
+
+/// Returns the best moves (the `bm` operation) of an EPD line, if they are
+/// legal on `board`
+///
+/// Moves that can't be read as SAN are left out.
+pub fn epd_best_moves(board: &Board, operations: &HashMap<String, String>) -> Vec<Move> {
+    match operations.get("bm") {
+        Some(bm) => bm.split_whitespace()
+            .filter_map(|san| Move::from_san(san, board))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Helper function, splits operations at the semicolons that end them,
+/// skipping over semicolons in quoted operands
+fn split_operations(text: &str) -> Result<Vec<&str>, EpdError> {
+    let mut operations = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                operations.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    let last = text[start..].trim();
+    if quoted {
+        return Err(EpdError::BadOperation(String::from(last)));
+    }
+    // The final semicolon is sometimes left out
+    if !last.is_empty() {
+        operations.push(last);
+    }
+    Ok(operations)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::helper::tables;
+
+    #[test]
+    fn test_parse_epd() {
+        tables::init();
+        let wac = "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";";
+        let (bd, ops) = parse_epd(wac).unwrap();
+        assert_eq!(bd.to_fen(), "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1");
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops["bm"], "Qg6");
+        assert_eq!(ops["id"], "WAC.001");
+        let best = epd_best_moves(&bd, &ops);
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].to_uci(bd.color()), "g3g6");
+        // Quoted operands may hold semicolons, and the last one may be unended
+        let (_, ops) = parse_epd("4k3/8/8/8/8/8/8/4K3 b - - c0 \"a; b\"; bm Kd7 Ke7").unwrap();
+        assert_eq!(ops["c0"], "a; b");
+        assert_eq!(ops["bm"], "Kd7 Ke7");
+        // Errors
+        assert_eq!(parse_epd("4k3/8/8/8/8/8/8/4K3 w -").unwrap_err(), EpdError::MissingFields(3));
+        assert_eq!(parse_epd("8/8/8/8/8/8/8/4K3 w - -").unwrap_err(),
+                   EpdError::Fen(FenError::KingCount(Color::Black, 0)));
+        assert_eq!(parse_epd("4k3/8/8/8/8/8/8/4K3 w - - id \"open;").unwrap_err(),
+                   EpdError::BadOperation(String::from("id \"open;")));
+    }
+}
@@ -0,0 +1,259 @@
+//! Checked construction of a [`Board`] from a FEN string
+
+use std::fmt;
+
+use super::*;
+#[cfg(feature = "serde")]
+use serde::{Deserializer, Serializer};
+
+/// FEN of the starting position
+pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Reasons a FEN string can be rejected by [`Board::from_fen`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FenError {
+    /// The FEN string does not have all six fields
+    MissingFields(usize),
+    /// The piece placement isn't eight ranks of eight squares each
+    BadPlacement,
+    /// The side to move is neither `w` nor `b`
+    BadSideToMove,
+    /// The castling field is neither `-` nor up to four castling rights
+    BadCastling,
+    /// The en passant field is neither `-` nor a square on the third or
+    /// sixth rank
+    BadEnPassant,
+    /// The halfmove clock or fullmove number isn't a number in range (the
+    /// fullmove number starts at 1)
+    BadClock,
+    /// A side does not have exactly one king
+    KingCount(Color, usize),
+    /// The position could never occur in a game
+    Illegal(IllegalPosition),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::MissingFields(n) =>
+                write!(f, "FEN has {} fields, expected 6", n),
+            FenError::BadPlacement => write!(f, "bad piece placement"),
+            FenError::BadSideToMove => write!(f, "bad side to move"),
+            FenError::BadCastling => write!(f, "bad castling rights"),
+            FenError::BadEnPassant => write!(f, "bad en passant square"),
+            FenError::BadClock => write!(f, "bad halfmove clock or fullmove number"),
+            FenError::KingCount(c, n) =>
+                write!(f, "{:?} has {} kings, expected exactly 1", c, n),
+            FenError::Illegal(e) => write!(f, "illegal position: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
+/// The starting position (see [`STARTPOS`]), building the lookup tables if
+/// they haven't been built yet
+impl Default for Board {
+    fn default() -> Self {
+        crate::helper::tables::init();
+        let mut board = Board::new();
+        board.board_from_fen(STARTPOS);
+        board
+    }
+}
+
+impl Board {
+    /// Creates a board from a FEN string, rejecting positions that are not
+    /// fit for normal play
+    ///
+    /// Unlike [`board_from_fen`](Board::board_from_fen), which will happily
+    /// load a position without kings (as some puzzle formats have), this
+    /// requires exactly one king per side, and rejects any other position
+    /// that [`validate`](Board::validate) does. Every field is checked
+    /// before anything is loaded, so malformed input (e.g. from a GUI) is
+    /// reported rather than panicking. The lookup tables must have been
+    /// built.
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 6 {
+            return Err(FenError::MissingFields(fields.len()));
+        }
+        check_placement(fields[0])?;
+        if !matches!(fields[1], "w" | "b") {
+            return Err(FenError::BadSideToMove);
+        }
+        check_castling(fields[2])?;
+        check_en_passant(fields[3])?;
+        let full_moves = fields[5].parse::<u16>().map_err(|_| FenError::BadClock)?;
+        // Larger fullmove numbers overflow the u16 count of halfmoves
+        if fields[4].parse::<u8>().is_err() || full_moves == 0 || full_moves > u16::MAX / 2 {
+            return Err(FenError::BadClock);
+        }
+        let placement = fields[0];
+        for (color, king) in [(Color::White, 'K'), (Color::Black, 'k')] {
+            let count = placement.chars().filter(|&c| c == king).count();
+            if count != 1 {
+                return Err(FenError::KingCount(color, count));
+            }
+        }
+        let mut board = Board::new();
+        board.board_from_fen(fen);
+        board.validate().map_err(FenError::Illegal)?;
+        Ok(board)
+    }
+}
+
+/// Serializes as the FEN string (see [`to_fen`](Board::to_fen))
+#[cfg(feature = "serde")]
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_fen())
+    }
+}
+
+/// Deserializes from a FEN string through [`from_fen`](Board::from_fen), so
+/// the lookup tables must have been built
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let fen = String::deserialize(deserializer)?;
+        Board::from_fen(&fen).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Helper function, checks that a placement field has eight ranks of eight
+/// squares, each a piece letter or a run of empty squares
+fn check_placement(placement: &str) -> Result<(), FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != Rank::COUNT {
+        return Err(FenError::BadPlacement);
+    }
+    for rank in ranks {
+        let mut files = 0;
+        for c in rank.chars() {
+            files += match c {
+                '1'..='8' => c as usize - '0' as usize,
+                _ if PieceType::from_char(c).is_some() => 1,
+                _ => return Err(FenError::BadPlacement),
+            };
+        }
+        if files != File::COUNT {
+            return Err(FenError::BadPlacement);
+        }
+    }
+    Ok(())
+}
+
+/// Helper function, checks that a castling field is `-` or up to four
+/// distinct rights, as `KQkq` or Shredder-FEN rook files
+fn check_castling(castling: &str) -> Result<(), FenError> {
+    if castling == "-" {
+        return Ok(());
+    }
+    let valid = |c: char| "KQkq".contains(c)
+        || ('A'..='H').contains(&c)
+        || ('a'..='h').contains(&c);
+    let mut seen = Vec::new();
+    for c in castling.chars() {
+        if !valid(c) || seen.contains(&c) {
+            return Err(FenError::BadCastling);
+        }
+        seen.push(c);
+    }
+    if seen.len() > 4 {
+        return Err(FenError::BadCastling);
+    }
+    Ok(())
+}
+
+/// Helper function, checks that an en passant field is `-` or a square on
+/// the third or sixth rank
+fn check_en_passant(en_passant: &str) -> Result<(), FenError> {
+    if en_passant == "-" {
+        return Ok(());
+    }
+    let chars: Vec<char> = en_passant.chars().collect();
+    match chars[..] {
+        ['a'..='h', '3' | '6'] => Ok(()),
+        _ => Err(FenError::BadEnPassant),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_from_fen() {
+        crate::helper::tables::init();
+        let fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap_err(),
+                   FenError::KingCount(Color::White, 0));
+        assert_eq!(Board::from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1").unwrap_err(),
+                   FenError::KingCount(Color::Black, 0));
+        assert_eq!(Board::from_fen("k7/8/8/8/8/8/8/4K1K1 w - - 0 1").unwrap_err(),
+                   FenError::KingCount(Color::White, 2));
+        assert_eq!(Board::from_fen("k7/8/8/8/8/8/8/4K3 w").unwrap_err(),
+                   FenError::MissingFields(2));
+        assert_eq!(Board::from_fen("4k3/8/8/8/8/8/8/P3K3 w - - 0 1").unwrap_err(),
+                   FenError::Illegal(IllegalPosition::PawnOnBackRank(Square::Sq(0))));
+        assert_eq!(Board::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap_err(),
+                   FenError::Illegal(IllegalPosition::OpponentInCheck));
+    }
+
+    #[test]
+    fn test_from_fen_malformed() {
+        crate::helper::tables::init();
+        let cases = [
+            ("4k3/8/8/8/8/8/4K3 w - - 0 1", FenError::BadPlacement),
+            ("4k3/8/8/8/8/8/8/9 w - - 0 1", FenError::BadPlacement),
+            ("4k3/8/8/8/8/8/8/4K2x w - - 0 1", FenError::BadPlacement),
+            ("4k3/8/8/8/8/8/8/4K3 x - - 0 1", FenError::BadSideToMove),
+            ("4k3/8/8/8/8/8/8/4K3 w Z - 0 1", FenError::BadCastling),
+            ("4k3/8/8/8/8/8/8/4K3 w KQkqK - 0 1", FenError::BadCastling),
+            ("4k3/8/8/8/8/8/8/4K3 w - z9 0 1", FenError::BadEnPassant),
+            ("4k3/8/8/8/8/8/8/4K3 w - e 0 1", FenError::BadEnPassant),
+            ("4k3/8/8/8/8/8/8/4K3 w - e4 0 1", FenError::BadEnPassant),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 0", FenError::BadClock),
+            ("4k3/8/8/8/8/8/8/4K3 w - - x 1", FenError::BadClock),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 y", FenError::BadClock),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 300 1", FenError::BadClock),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 40000", FenError::BadClock),
+        ];
+        for (fen, err) in cases {
+            assert_eq!(Board::from_fen(fen).unwrap_err(), err, "{}", fen);
+        }
+        // Shredder-FEN castling is still fine, even mixed with `KQkq`
+        assert!(Board::from_fen("4k3/8/8/8/8/8/8/R3K2R w HA - 0 1").is_ok());
+        let bd = Board::from_fen("4k2r/8/8/8/8/8/8/4K2R w Kh - 0 1").unwrap();
+        assert_eq!(bd.to_fen(), "4k2r/8/8/8/8/8/8/4K2R w Hh - 0 1");
+    }
+
+    #[test]
+    fn test_from_fen_wrong_shape() {
+        crate::helper::tables::init();
+        for fen in ["4k3/8/8/8/8/8/4K3 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/8/4K3 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4K4 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+                    "4k3/8/8/8/8/8/8/4K3/ w - - 0 1"] {
+            assert_eq!(Board::from_fen(fen).unwrap_err(), FenError::BadPlacement, "{}", fen);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        crate::helper::tables::init();
+        let bd = Board::from_fen(STARTPOS).unwrap();
+        let json = serde_json::to_string(&bd).unwrap();
+        assert_eq!(json, format!("\"{}\"", STARTPOS));
+        assert_eq!(serde_json::from_str::<Board>(&json).unwrap(), bd);
+        let m = generate_legal(&bd)[0];
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<Move>(&json).unwrap(), m);
+        assert!(serde_json::from_str::<Board>("\"8/8/8/8 w - - 0 1\"").is_err());
+    }
+}
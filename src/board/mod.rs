@@ -1,21 +1,45 @@
 //! Represents a chess board and provides an interface for changing the state
 //! of the board.
 //!
-//!
+//! Like the rest of the crate, methods panic on `Null` squares and pieces;
+//! [`Board::try_get`], [`Board::try_set`], [`Board::try_move_piece`], and
+//! [`Board::try_apply`] report bad input instead.
 
+mod attacks;
 pub mod bits;
-mod castling;
+mod builder;
+pub mod castling;
+mod epd;
+mod fen;
+mod result;
+mod see;
 mod square_lut;
+#[cfg(feature = "debug-trace")]
+pub mod trace;
 mod util;
+mod validate;
+pub mod variant;
+mod zobrist;
 
-use crate::movegen::Move;
-use crate::helper::{king_origin, rook_origin};
+use crate::eval;
+use crate::movegen::{AnnotatedMove, Move, generate_captures, generate_legal, generate_pseudo_legal};
+use crate::helper::back_rank_square;
 use bits::{File, Rank, Square, Bitboard};
+pub use builder::BoardBuilder;
 use castling::Castling;
+pub use epd::{EpdError, epd_best_moves, parse_epd};
+pub use fen::{FenError, STARTPOS};
+pub use result::{DrawReason, GameResult};
 use square_lut::SquareLUT;
+use util::PRINT_ORDER;
+pub use validate::IllegalPosition;
+use variant::Variant;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// The six piece types in chess
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PieceType {
     P,
     N,
@@ -34,11 +58,45 @@ impl PieceType {
     /// Used when iterating over piece bitboards 
     /// (the king doesn't get a bitboard)
     pub const NK_COUNT: usize = 5usize;
+
+    /// Returns an iterator over all six piece types (in index order)
+    pub fn all() -> impl Iterator<Item = PieceType> {
+        [PieceType::P, PieceType::N, PieceType::B,
+         PieceType::R, PieceType::Q, PieceType::K].into_iter()
+    }
+
+    /// Converts a letter (in either case) into a piece type
+    pub fn from_char(c: char) -> Option<PieceType> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(PieceType::P),
+            'n' => Some(PieceType::N),
+            'b' => Some(PieceType::B),
+            'r' => Some(PieceType::R),
+            'q' => Some(PieceType::Q),
+            'k' => Some(PieceType::K),
+            _ => None,
+        }
+    }
+
+    /// Returns the material value of a piece type in centipawns
+    /// 
+    /// The king is priceless, so it is worth nothing
+    pub fn value(&self) -> i32 {
+        match self {
+            PieceType::P => 100,
+            PieceType::N => 320,
+            PieceType::B => 330,
+            PieceType::R => 500,
+            PieceType::Q => 900,
+            PieceType::K => 0,
+        }
+    }
 }
 
 
 /// Enum for colorless piece representation
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Whose {
     Ours,
     Theirs,
@@ -48,6 +106,24 @@ impl Whose {
     // Number of sides to the game (two sides, duh)
     pub const COUNT: usize = 2usize;
 
+    /// Returns an iterator over both sides (in index order)
+    pub fn all() -> impl Iterator<Item = Whose> {
+        [Whose::Ours, Whose::Theirs].into_iter()
+    }
+
+    /// Returns the absolute color of a side, given `pov` is the color of
+    /// the side to move
+    pub fn to_color(&self, pov: Color) -> Color {
+        match self {
+            Whose::Ours => pov,
+            Whose::Theirs => {
+                let mut c = pov;
+                c.flip();
+                c
+            }
+        }
+    }
+
     pub fn flip (&mut self) -> () {
         match *self {
             Whose::Ours => *self = Whose::Theirs,
@@ -58,6 +134,7 @@ impl Whose {
 
 /// Tuple of [`PieceType`] and [`Whose`]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Piece {
     Null,
     Empty,
@@ -85,23 +162,65 @@ impl Piece {
         }
     }
 
+    /// Converts a character back into a piece (the inverse of
+    /// [`to_char`](Piece::to_char))
+    /// 
+    /// Uppercase letters are ours, lowercase letters are theirs, and `'.'` is
+    /// an empty square
+    pub fn from_char(c: char) -> Option<Piece> {
+        match c {
+            '.' => Some(Piece::Empty),
+            'P' => Some(Piece::Pc(Whose::Ours, PieceType::P)),
+            'N' => Some(Piece::Pc(Whose::Ours, PieceType::N)),
+            'B' => Some(Piece::Pc(Whose::Ours, PieceType::B)),
+            'R' => Some(Piece::Pc(Whose::Ours, PieceType::R)),
+            'Q' => Some(Piece::Pc(Whose::Ours, PieceType::Q)),
+            'K' => Some(Piece::Pc(Whose::Ours, PieceType::K)),
+            'p' => Some(Piece::Pc(Whose::Theirs, PieceType::P)),
+            'n' => Some(Piece::Pc(Whose::Theirs, PieceType::N)),
+            'b' => Some(Piece::Pc(Whose::Theirs, PieceType::B)),
+            'r' => Some(Piece::Pc(Whose::Theirs, PieceType::R)),
+            'q' => Some(Piece::Pc(Whose::Theirs, PieceType::Q)),
+            'k' => Some(Piece::Pc(Whose::Theirs, PieceType::K)),
+            _ => None,
+        }
+    }
+
     pub fn is_whose(&self, w: Whose) -> bool {
         match *self {
-            Piece::Pc(w_, _) => (w == w_),
+            Piece::Pc(w_, _) => w == w_,
             Piece::Empty => false,
             Piece::Null => panic!("Attempted to check Whose of null piece")
         }
     }
 
+    /// Returns the type of the piece, or `None` for an empty square
+    pub fn piece_type(&self) -> Option<PieceType> {
+        match *self {
+            Piece::Pc(_, pt) => Some(pt),
+            Piece::Empty => None,
+            Piece::Null => panic!("Attempted to get PieceType of null piece")
+        }
+    }
+
     pub fn is_piecetype(&self, pt: PieceType) -> bool {
         match *self {
-            Piece::Pc(_, pt_) => (pt == pt_),
+            Piece::Pc(_, pt_) => pt == pt_,
             Piece::Empty => false,
             Piece::Null => panic!("Attempted to check PieceType of null piece")
         }
     }
 }
 
+/// Reads a piece from its character, as [`Piece::from_char`] does
+impl TryFrom<char> for Piece {
+    type Error = bits::OutOfRange<char>;
+
+    fn try_from(c: char) -> Result<Piece, Self::Error> {
+        Piece::from_char(c).ok_or(bits::OutOfRange(c))
+    }
+}
+
 /// Denotes whose turn it is
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Color {
@@ -110,6 +229,12 @@ pub enum Color {
 }
 
 impl Color {
+    /// Returns whose side a color is on, given `pov` is the color of the
+    /// side to move
+    pub fn to_whose(&self, pov: Color) -> Whose {
+        if *self == pov { Whose::Ours } else { Whose::Theirs }
+    }
+
     /// Flips a Color
     pub fn flip(&mut self) -> () {
         match *self {
@@ -121,12 +246,54 @@ impl Color {
 
 /// Represents the two sides where one can castle
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Side { K, Q }
 
+/// Reasons a move can be refused by [`Board::try_apply`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveError {
+    /// There is none of our pieces on the origin square
+    NoPiece(Square),
+    /// The destination square holds one of our own pieces
+    FriendlyCapture(Square),
+    /// A piece was dropped from a pocket without any of its type
+    EmptyPocket(PieceType),
+    /// A piece was dropped onto a square that isn't empty
+    Occupied(Square),
+}
+
+impl std::fmt::Display for MoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MoveError::NoPiece(sq) => write!(f, "no piece of ours on {}", sq),
+            MoveError::FriendlyCapture(sq) => write!(f, "{} holds one of our pieces", sq),
+            MoveError::EmptyPocket(pt) => write!(f, "no {:?} in our pocket", pt),
+            MoveError::Occupied(sq) => write!(f, "{} is not empty", sq),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Everything needed to take back a move made with [`Board::make`]
+#[derive(Clone, Copy, Debug)]
+pub struct Undo {
+    /// The board as it was before the move
+    board: Board,
+}
+
+/// Everything needed to take back a null move made with
+/// [`Board::make_null`]
+#[derive(Clone, Copy, Debug)]
+pub struct NullUndo {
+    /// The en passant square before the null move
+    en_passant: Square,
+}
+
 /// All the components combined to represent a chess board
 ///
 /// 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub struct Board {
     pub whose_bbs: [Bitboard; Whose::COUNT],
     pub piece_type_bbs: [Bitboard; PieceType::NK_COUNT],
@@ -135,8 +302,31 @@ pub struct Board {
     castling: Castling,
     color: Color,
     en_passant: Square,
-    half_moves: u8,
+    half_moves: u16,
     rule50: u8,
+    /// Zobrist hash of the position, updated incrementally
+    hash: u64,
+    variant: Variant,
+    /// Whether or not castling follows Chess960 (Fischer Random) notation
+    chess960: bool,
+    /// Number of checks each side has given (see [`Variant::ThreeCheck`])
+    checks: [u8; Whose::COUNT],
+    /// Captured pieces each side can drop (see [`Variant::Crazyhouse`]),
+    /// indexed by piece type
+    pockets: [[u8; PieceType::NK_COUNT]; Whose::COUNT],
+    /// Pieces that got there by promoting, which go back into a pocket as
+    /// pawns when captured
+    promoted: Bitboard,
+}
+
+/// Hashes a board by its Zobrist hash, so boards can be used as keys
+///
+/// The Zobrist hash leaves out the move counters, so positions that only
+/// differ in them (which count as the same for repetitions) hash equally.
+impl std::hash::Hash for Board {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) -> () {
+        self.hash.hash(state);
+    }
 }
 
 impl Board {
@@ -153,15 +343,56 @@ impl Board {
             castling: Castling::EMPTY, 
             color: Color::White, 
             en_passant: Square::Null, 
-            half_moves: 0u8, 
+            half_moves: 0u16, 
             rule50: 0u8, 
+            hash: 0u64,
+            variant: Variant::Standard,
+            chess960: false,
+            checks: [0u8; Whose::COUNT],
+            pockets: [[0u8; PieceType::NK_COUNT]; Whose::COUNT],
+            promoted: Bitboard::EMPTY,
         }
     }
 
+    /// Removes every piece along with the castling rights, en passant
+    /// square, move counters, checks given, and pockets
+    ///
+    /// Unlike [`board_clear`](Board::board_clear), the color to move and the
+    /// variant are kept.
     pub fn clear(&mut self) -> () {
         for wbb in &mut self.whose_bbs { *wbb = Bitboard::EMPTY; }
         for ptbb in &mut self.piece_type_bbs { *ptbb = Bitboard::EMPTY; }
+        self.kings = [Square::Null; Whose::COUNT];
         self.sq_lut.clear();
+        self.castling = Castling::EMPTY;
+        self.en_passant = Square::Null;
+        self.half_moves = 0u16;
+        self.rule50 = 0u8;
+        self.checks = [0u8; Whose::COUNT];
+        self.pockets = [[0u8; PieceType::NK_COUNT]; Whose::COUNT];
+        self.promoted = Bitboard::EMPTY;
+        self.hash = self.zobrist_hash();
+    }
+
+    /// Gets the piece at a [Square](crate::board::bits::Square), or `None`
+    /// for `Square::Null` (where [`get`](Board::get) would panic)
+    pub fn try_get(&self, sq: Square) -> Option<Piece> {
+        match sq {
+            Square::Null => None,
+            _ => Some(self.get(sq)),
+        }
+    }
+
+    /// Sets a square like [`set`](Board::set), but returns `None` (leaving
+    /// the board unchanged) for `Square::Null` or `Piece::Null`
+    pub fn try_set(&mut self, sq: Square, p: Piece) -> Option<()> {
+        match (sq, p) {
+            (Square::Null, _) | (_, Piece::Null) => None,
+            _ => {
+                self.set(sq, p);
+                Some(())
+            }
+        }
     }
 
     /// Gets the piece at a [Square](crate::board::bits::Square)
@@ -206,6 +437,7 @@ impl Board {
                         }
                     }
                 }
+                self.hash ^= self.piece_key(sq, p_prev) ^ self.piece_key(sq, p);
                 self.sq_lut.set(sq, p);
             }
         }
@@ -226,6 +458,12 @@ impl Board {
     /// pieces on them
     pub fn get_pieces(&self, p: Piece) -> Bitboard {
         match p {
+            Piece::Pc(w, PieceType::K) => {
+                match self.kings[w as usize] {
+                    Square::Null => Bitboard::EMPTY,
+                    king => king.to_bitboard(),
+                }
+            }
             Piece::Pc(w, pt) => {
                 self.whose_bbs[w as usize] & self.piece_type_bbs[pt as usize]
             }
@@ -234,6 +472,210 @@ impl Board {
         }
     }
 
+    /// Returns the color of the side to move
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Returns the number of halfmoves played since the start of the game
+    pub fn half_moves(&self) -> u16 {
+        self.half_moves
+    }
+
+    /// Returns the castling rights (relative to the side to move)
+    pub fn castling_rights(&self) -> Castling {
+        self.castling
+    }
+
+    /// Returns the square a pawn can capture en passant onto (or 
+    /// `Square::Null` if there is none)
+    pub fn en_passant(&self) -> Square {
+        self.en_passant
+    }
+
+    /// Returns the number of halfmoves since the last pawn move or capture
+    pub fn rule50(&self) -> u8 {
+        self.rule50
+    }
+
+    /// Returns whether or not a draw can be claimed by the fifty-move rule
+    /// (i.e. 100 halfmoves without a pawn move or capture)
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.rule50 >= 100
+    }
+
+    /// Returns the total value of `w`'s pieces in centipawns
+    pub fn material(&self, w: Whose) -> i32 {
+        PieceType::all()
+            .map(|pt| self.piece_count(Piece::Pc(w, pt)) as i32 * pt.value())
+            .sum()
+    }
+
+    /// Returns the number of pieces `p` on the board
+    pub fn piece_count(&self, p: Piece) -> u8 {
+        self.get_pieces(p).pop_count()
+    }
+
+    /// Returns the game phase, from 24 with all of the pieces on the board
+    /// down to 0 when only kings and pawns remain
+    /// 
+    /// Minor pieces count 1, rooks 2, and queens 4 (capped at 24 in case of
+    /// promotions), for tapering between middlegame and endgame evaluations
+    pub fn phase(&self) -> i32 {
+        let mut phase = 0i32;
+        for w in Whose::all() {
+            for (pt, weight) in [(PieceType::N, 1), (PieceType::B, 1),
+                                 (PieceType::R, 2), (PieceType::Q, 4)] {
+                phase += self.piece_count(Piece::Pc(w, pt)) as i32 * weight;
+            }
+        }
+        phase.min(24)
+    }
+
+    /// Returns a quick estimate of the evaluation, from the side to move's
+    /// point of view
+    /// 
+    /// Only material is counted, so this is cheap enough to screen positions
+    /// before calling [`eval::evaluate`](crate::eval::evaluate) when the
+    /// estimate is close to the search window
+    pub fn lazy_eval(&self) -> i32 {
+        eval::evaluate_material(self)
+    }
+
+    /// Returns every legal move along with its SAN and whether it gives
+    /// check, gives mate, or captures
+    pub fn annotated_moves(&self) -> Vec<AnnotatedMove> {
+        generate_legal(self)
+            .into_iter()
+            .map(|m| AnnotatedMove::new(self, m))
+            .collect()
+    }
+
+    /// Returns the number of legal captures (including en passant)
+    pub fn legal_capture_count(&self) -> usize {
+        generate_captures(self).len()
+    }
+
+    /// Returns the number of pseudo-legal moves `w` could make if it were
+    /// their turn, as a rough measure of how active their pieces are
+    pub fn mobility(&self, w: Whose) -> u32 {
+        let mut bd = *self;
+        if w == Whose::Theirs {
+            bd.flip();
+        }
+        generate_pseudo_legal(&bd).len() as u32
+    }
+
+    /// Returns every legal capture, best first by static exchange evaluation
+    /// (see [`see`](Board::see))
+    pub fn captures_ordered(&self) -> Vec<Move> {
+        let mut captures: Vec<(Move, i32)> = generate_captures(self)
+            .into_iter()
+            .map(|m| (m, self.see(m)))
+            .collect();
+        captures.sort_by_key(|&(_, see)| -see);
+        captures.into_iter().map(|(m, _)| m).collect()
+    }
+
+    /// Returns whether or not `other` is the same position for the purpose
+    /// of repetitions: the same pieces on the same squares (and in the same
+    /// pockets), the same side to move, castling rights, and en passant
+    /// square
+    ///
+    /// Unlike `==`, the move counters are ignored.
+    pub fn same_position(&self, other: &Board) -> bool {
+        self.sq_lut == other.sq_lut
+            && self.color == other.color
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+            && self.pockets == other.pockets
+    }
+
+    /// Returns whether or not the bitboards, the king squares, and the square
+    /// lookup table all agree on where every piece is, and each side has
+    /// exactly one king
+    ///
+    /// Meant for `debug_assert!`s after editing a board by hand
+    pub fn is_consistent(&self) -> bool {
+        let mut king_count = [0usize; Whose::COUNT];
+        for sq in Square::all() {
+            let (w, pt) = match self.sq_lut.get(sq) {
+                Piece::Null => return false,
+                Piece::Empty => {
+                    if self.get_all().get(sq)
+                        || self.piece_type_bbs.iter().any(|bb| bb.get(sq)) {
+                        return false;
+                    }
+                    continue;
+                }
+                Piece::Pc(w, pt) => (w, pt),
+            };
+            let mut other = w;
+            other.flip();
+            if !self.whose_bbs[w as usize].get(sq) || self.whose_bbs[other as usize].get(sq) {
+                return false;
+            }
+            for other_pt in PieceType::all().filter(|&p| p != PieceType::K) {
+                if self.piece_type_bbs[other_pt as usize].get(sq) != (other_pt == pt) {
+                    return false;
+                }
+            }
+            if pt == PieceType::K {
+                if self.kings[w as usize] != sq {
+                    return false;
+                }
+                king_count[w as usize] += 1;
+            }
+        }
+        king_count == [1, 1] && self.sq_lut.count() == self.get_all().pop_count() as usize
+    }
+
+    /// Returns whether or not the side to move has been checkmated
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check() && generate_legal(self).is_empty()
+    }
+
+    /// Returns whether or not the side to move has been stalemated
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check() && generate_legal(self).is_empty()
+    }
+
+    /// Returns whether or not neither side can ever be checkmated, so the
+    /// game is drawn by dead position
+    ///
+    /// Only material is looked at, so this stays cheap and conservative. The
+    /// cases covered, with no pawns, rooks, or queens on the board, are:
+    /// - king against king
+    /// - king and a single knight or bishop against king
+    /// - kings and any number of bishops, all on squares of one color
+    /// - king and two knights against king, which can't force mate (a mate
+    ///   needs the lone king to blunder into it, so this is the one case
+    ///   that is a draw in practice rather than by the letter of the rules)
+    ///
+    /// Blocked pawn fortresses are not detected.
+    pub fn is_dead_position(&self) -> bool {
+        const DARK_SQUARES: Bitboard = Bitboard::Bb(0xAA55AA55AA55AA55);
+        let count = |w: Whose, pt: PieceType| self.piece_count(Piece::Pc(w, pt));
+        let heavy_or_pawn = Whose::all().any(|w| {
+            [PieceType::P, PieceType::R, PieceType::Q].into_iter().any(|pt| count(w, pt) > 0)
+        });
+        if heavy_or_pawn {
+            return false;
+        }
+        let minors = |w: Whose| count(w, PieceType::N) + count(w, PieceType::B);
+        if minors(Whose::Ours) + minors(Whose::Theirs) <= 1 {
+            return true;
+        }
+        if count(Whose::Ours, PieceType::N) + count(Whose::Theirs, PieceType::N) == 0 {
+            let bishops = self.get_pieces(Piece::Pc(Whose::Ours, PieceType::B))
+                | self.get_pieces(Piece::Pc(Whose::Theirs, PieceType::B));
+            return (bishops & DARK_SQUARES).is_empty() || (bishops & !DARK_SQUARES).is_empty();
+        }
+        let two_knights = |w: Whose| count(w, PieceType::N) == 2 && count(w, PieceType::B) == 0;
+        (two_knights(Whose::Ours) && minors(Whose::Theirs) == 0)
+            || (two_knights(Whose::Theirs) && minors(Whose::Ours) == 0)
+    }
+
     /// Moves a [Piece] from a square to another square
     pub fn move_piece(&mut self, to: Square, from: Square) -> () {
         let p_from = self.get(from);
@@ -249,24 +691,132 @@ impl Board {
         }
     }
 
-    /// Updates the board given a valid move
+    /// Makes a move and hands the turn to the opponent, returning what is
+    /// needed to take the move back with [`unmake`](Board::unmake)
+    /// 
+    /// Meant for search, so the move is not traced
+    pub fn make(&mut self, m: Move) -> Undo {
+        let undo = Undo { board: *self };
+        self.play(m);
+        undo
+    }
+
+    /// Takes back the move that returned `undo`
+    pub fn unmake(&mut self, undo: Undo) -> () {
+        *self = undo.board;
+    }
+
+    /// Moves one of our pieces like [`move_piece`](Board::move_piece), but
+    /// refuses to capture one of our own pieces
+    /// 
+    /// The board is left unchanged on error
+    pub fn try_move_piece(&mut self, to: Square, from: Square) -> Result<(), MoveError> {
+        self.check_move_squares(to, from)?;
+        self.move_piece(to, from);
+        Ok(())
+    }
+
+    /// Applies a move like [`apply`](Board::apply), but refuses moves that
+    /// would capture one of our own pieces
+    /// 
+    /// Castling is exempt since the king and rook trade places around each
+    /// other. Drops are refused if the pocket is empty or the square isn't.
+    /// The board is left unchanged on error.
+    pub fn try_apply(&mut self, m: Move) -> Result<(), MoveError> {
+        if let Some(pt) = m.drop {
+            if self.pocket(Whose::Ours, pt) == 0 {
+                return Err(MoveError::EmptyPocket(pt));
+            }
+            if m.to.is_null() || self.get(m.to) != Piece::Empty {
+                return Err(MoveError::Occupied(m.to));
+            }
+        } else if m.castling.is_none() {
+            self.check_move_squares(m.to, m.from)?;
+        }
+        self.apply(m);
+        Ok(())
+    }
+
+    /// Helper function, checks that `from` holds one of our pieces and `to`
+    /// doesn't
+    fn check_move_squares(&self, to: Square, from: Square) -> Result<(), MoveError> {
+        if from.is_null() || !self.get(from).is_whose(Whose::Ours) {
+            return Err(MoveError::NoPiece(from));
+        }
+        if to.is_null() || self.get(to).is_whose(Whose::Ours) {
+            return Err(MoveError::FriendlyCapture(to));
+        }
+        Ok(())
+    }
+
+    /// Updates the board given a valid move and hands the turn to the
+    /// opponent
+    /// 
+    /// The move counters are kept up to date: the half move count goes up by
+    /// one, and the fifty-move clock is reset by pawn moves and captures (and
+    /// goes up by one otherwise).
     pub fn apply(&mut self, m: Move) -> () {
-        if m.castling.is_some() {
-            let cs = m.castling.unwrap();
+        #[cfg(feature = "debug-trace")]
+        let color = self.color;
+        self.play(m);
+        #[cfg(feature = "debug-trace")]
+        trace::record(&m.to_uci(color), &self.to_fen());
+    }
+
+    /// Helper function, does the work of [`apply`](Board::apply) without
+    /// tracing
+    pub(crate) fn play(&mut self, m: Move) -> () {
+        self.apply_move(m);
+        self.half_moves += 1;
+        self.flip();
+    }
+
+    /// Helper function, moves the pieces for [`apply`](Board::apply) but
+    /// leaves the turn (and the half move count) alone, for moves that are
+    /// only being tried out (e.g. legality checks)
+    pub(crate) fn apply_move(&mut self, m: Move) -> () {
+        // En passant is only ever available for a single move
+        let en_passant = self.en_passant;
+        self.set_en_passant(Square::Null);
+        if let Some(pt) = m.drop {
+            // A dropped piece can never go back into the pocket either
+            self.rule50 = 0u8;
+            self.apply_drop(m.to, pt);
+            self.record_check();
+            return;
+        }
+        // Pawn moves and captures are irreversible and reset the clock
+        let is_capture = m.captured.is_some();
+        if m.piece == PieceType::P || is_capture {
+            self.rule50 = 0u8;
+        } else {
+            self.rule50 = self.rule50.saturating_add(1);
+        }
+        if self.variant == Variant::Crazyhouse {
+            self.pocket_capture(m);
+        }
+        if let Some(cs) = m.castling {
             self.apply_castling(cs);
-        } else if m.promotion.is_some() {
+        } else if let Some(promo_pt) = m.promotion {
+            self.revoke_rook_castling(m.to);
             self.set(m.from, Piece::Empty);
-            let promo_pt = m.promotion.unwrap();
             self.set(m.to, Piece::Pc(Whose::Ours, promo_pt));
         } else {
-            let moved_piece = self.get(m.from);
+            let is_en_passant = m.piece == PieceType::P
+                && m.to == en_passant
+                && m.to.file() != m.from.file()
+                && self.get(m.to) == Piece::Empty;
+            if is_en_passant {
+                // The captured pawn sits right behind the en passant square
+                self.set(m.to.rank_down(), Piece::Empty);
+            }
+            self.revoke_rook_castling(m.to);
             self.move_piece(m.to, m.from);
-            match moved_piece {
-                Piece::Pc(Whose::Ours, PieceType::K) => 
-                    self.castling_reset_both(Whose::Ours),
-                Piece::Pc(Whose::Ours, PieceType::R) => {
+            match m.piece {
+                PieceType::K => self.castling_reset_both(Whose::Ours),
+                PieceType::R => {
                     for side in [Side::K, Side::Q] {
-                        let origin = rook_origin(side, Whose::Ours, self.color);
+                        let origin = self.castling_rook(side, Whose::Ours);
                         let castling = self.castling_get(Whose::Ours, side);
                         if castling && m.from == origin {
                             self.castling_reset(Whose::Ours, side);
@@ -276,77 +826,258 @@ impl Board {
                 _ => ()
             }
             if m.dpp {
-                debug_assert_eq!(moved_piece, Piece::Pc(Whose::Ours, PieceType::P));
+                debug_assert_eq!(m.piece, PieceType::P);
                 let left = m.to.file_down();
                 let right = m.to.file_up();
                 let their_pawn = Piece::Pc(Whose::Theirs, PieceType::P);
-                for sq in [left, right] {
-                    match sq {
-                        Square::Sq(_) => {
-                            if self.get(sq) == their_pawn {
-                                self.en_passant = m.to.rank_down();
-                            } else {
-                                self.en_passant = Square::Null;
-                            }
-                        }
-                        Square::Null => ()
-                    }
+                let capturable = [left, right].iter()
+                    .any(|&sq| !sq.is_null() && self.get(sq) == their_pawn);
+                if capturable {
+                    self.set_en_passant(m.to.rank_down());
                 }
             }
         }
+        if is_capture && self.variant == Variant::Atomic {
+            self.explode(m.to);
+        }
+        self.record_check();
+    }
+
+    /// Returns whether or not the side to move may pass with a null move
+    /// 
+    /// Passing while in check would leave our king capturable, so null-move
+    /// pruning must be skipped in check
+    pub fn can_make_null_move(&self) -> bool {
+        !self.is_in_check()
+    }
+
+    /// Passes the turn to the opponent without moving a piece
+    /// 
+    /// The side to move must not be in check (see
+    /// [`can_make_null_move`](Board::can_make_null_move)). If it is, the
+    /// null move is refused and the board is left unchanged. Returns whether
+    /// or not the null move was made.
+    pub fn make_null_move(&mut self) -> bool {
+        if !self.can_make_null_move() {
+            return false;
+        }
+        self.set_en_passant(Square::Null);
+        self.flip();
+        true
+    }
+
+    /// Makes a null move like [`make_null_move`](Board::make_null_move),
+    /// returning what is needed to take it back with
+    /// [`unmake_null`](Board::unmake_null)
+    ///
+    /// This is how [`Move::NULL`] is played; [`make`](Board::make) only takes
+    /// real moves.
+    ///
+    /// Panics if the side to move is in check, since passing would leave
+    /// the king capturable.
+    pub fn make_null(&mut self) -> NullUndo {
+        let undo = NullUndo { en_passant: self.en_passant };
+        if !self.make_null_move() {
+            panic!("Attempted to make a null move while in check");
+        }
+        undo
+    }
+
+    /// Takes back the null move that returned `undo`
+    pub fn unmake_null(&mut self, undo: NullUndo) -> () {
+        self.flip();
+        self.set_en_passant(undo.en_passant);
+    }
+
+    /// Helper function, sets the en passant square and updates the hash
+    fn set_en_passant(&mut self, sq: Square) -> () {
+        self.hash ^= self.en_passant_key();
+        self.en_passant = sq;
+        self.hash ^= self.en_passant_key();
+    }
+
+    /// Helper function, revokes their castling rights if `sq` holds one of
+    /// their rooks on its original square
+    fn revoke_rook_castling(&mut self, sq: Square) -> () {
+        if self.get(sq) != Piece::Pc(Whose::Theirs, PieceType::R) {
+            return;
+        }
+        for side in [Side::K, Side::Q] {
+            if sq == self.castling_rook(side, Whose::Theirs) {
+                self.castling_reset(Whose::Theirs, side);
+            }
+        }
     }
 
     /// Helper function, applies castling to the board
+    /// 
+    /// Both pieces go through [`set`](Board::set), which keeps the bitboards,
+    /// the king slot and the hash in sync. They are lifted off the board
+    /// before either is put down, since in Chess960 the king may land where
+    /// the rook started (or the other way around). Castling never captures,
+    /// so only our own castling rights change.
     fn apply_castling (&mut self, cs: Side) -> () {
         debug_assert!(self.castling_get(Whose::Ours, cs));
-        let king = match self.color {
-            Color::White => Square::from(File::E, Rank::First),
-            Color::Black => Square::from(File::E, Rank::Eighth)
-        };
-        let rook = match (self.color, cs) {
-            (Color::White, Side::K) => Square::from(File::H, Rank::First),
-            (Color::White, Side::Q) => Square::from(File::A, Rank::First),
-            (Color::Black, Side::K) => Square::from(File::H, Rank::Eighth),
-            (Color::Black, Side::Q) => Square::from(File::A, Rank::Eighth),
-        };
-        let (king_new, rook_new) = match cs {
-            Side::K => (king.offset(2i8, 0), rook.offset(-2i8, 0)),
-            Side::Q => (king.offset(-2i8, 0), rook.offset(3i8, 0)),
-        };
-        self.move_piece(king_new, king);
-        self.move_piece(rook_new, rook);
+        let (king, king_new, rook, rook_new) = self.castling_squares(cs);
+        self.set(king, Piece::Empty);
+        self.set(rook, Piece::Empty);
+        self.set(king_new, Piece::Pc(Whose::Ours, PieceType::K));
+        self.set(rook_new, Piece::Pc(Whose::Ours, PieceType::R));
         self.castling_reset_both(Whose::Ours);
     }
 
+    /// Returns the squares involved in our castling to side `cs` as
+    /// `(king origin, king destination, rook origin, rook destination)`
+    /// 
+    /// Wherever the king and rook start, the king always lands on the g-file
+    /// (kingside) or c-file (queenside), and the rook right next to it on the
+    /// f-file or d-file. Since the board is rotated for Black, castling
+    /// kingside moves our king towards the a-file when we are Black.
+    pub fn castling_squares(&self, cs: Side) -> (Square, Square, Square, Square) {
+        let (king_file, rook_file) = match cs {
+            Side::K => (File::G, File::F),
+            Side::Q => (File::C, File::D),
+        };
+        (self.kings[Whose::Ours as usize],
+         back_rank_square(king_file, Whose::Ours, self.color),
+         self.castling_rook(cs, Whose::Ours),
+         back_rank_square(rook_file, Whose::Ours, self.color))
+    }
+
+    /// Returns the square the rook of `w` castling to side `cs` starts on
+    /// (relative to the side to move)
+    pub fn castling_rook(&self, cs: Side, w: Whose) -> Square {
+        back_rank_square(self.castling.rook_file(w, cs), w, self.color)
+    }
+
+    /// Returns whether or not castling follows Chess960 (Fischer Random)
+    /// notation
+    /// 
+    /// In Chess960 a castling move is written as the king capturing its own
+    /// rook (e.g. `e1h1` in UCI), and the FEN castling field names the rook
+    /// files (Shredder-FEN, e.g. `HAha`).
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+
+    /// Sets whether or not castling follows Chess960 notation
+    pub fn set_chess960(&mut self, chess960: bool) -> () {
+        self.chess960 = chess960;
+    }
+
     /// Flips an entire board
     pub fn flip (&mut self) -> () {
         for wbb in &mut self.whose_bbs { wbb.flip(); }
         for ptbb in &mut self.piece_type_bbs { ptbb.flip(); }
-        for k in &mut self.kings { k.flip(); }
+        for k in &mut self.kings {
+            if !k.is_null() { k.flip(); }
+        }
+        // Our pieces become theirs and vice versa
+        self.whose_bbs.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.kings.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.checks.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.pockets.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.promoted.flip();
         self.sq_lut.flip();
         self.castling.flip();
         self.color.flip();
+        if !self.en_passant.is_null() {
+            self.en_passant.flip();
+        }
+        // Every other key is absolute, so only the side to move changes
+        self.hash ^= zobrist::SIDE_KEY;
+        self.clear_meaningless_en_passant();
+    }
+
+    /// Helper function, clears the en passant square unless one of our pawns
+    /// could capture onto it, so that equal positions hash equally
+    fn clear_meaningless_en_passant(&mut self) -> () {
+        let ep = self.en_passant;
+        if ep.is_null() {
+            return;
+        }
+        let meaningful = ep.rank() == Rank::Sixth
+            && self.get(ep.rank_down()) == Piece::Pc(Whose::Theirs, PieceType::P)
+            && [-1, 1].iter().any(|&dx| {
+                let sq = ep.offset(dx, -1);
+                !sq.is_null() && self.get(sq) == Piece::Pc(Whose::Ours, PieceType::P)
+            });
+        if !meaningful {
+            self.set_en_passant(Square::Null);
+        }
+    }
+
+    /// Hands the move to the other player without moving any pieces
+    /// 
+    /// Ours and theirs are swapped (along with the color to move and the
+    /// castling rights) but, unlike [`flip`](Board::flip), every piece stays
+    /// on its current square. The en passant square belongs to a move that no
+    /// longer happened, so it is cleared.
+    pub fn swap_sides(&mut self) -> () {
+        self.whose_bbs.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.kings.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.checks.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.pockets.swap(Whose::Ours as usize, Whose::Theirs as usize);
+        self.sq_lut.swap_whose();
+        self.castling.flip();
+        self.color.flip();
+        self.en_passant = Square::Null;
+        self.hash = self.zobrist_hash();
     }
 
     /// Gets castling rights
-    pub fn castling_get(&mut self, w: Whose, cs: Side) -> bool {
+    pub fn castling_get(&self, w: Whose, cs: Side) -> bool {
         self.castling.get(w, cs)
     }
 
     /// Sets castling rights
     pub fn castling_set(&mut self, w: Whose, cs: Side) -> () {
-        self.castling.set(w, cs)
+        self.hash ^= self.castling_key();
+        self.castling.set(w, cs);
+        self.hash ^= self.castling_key();
     }
 
     /// Resets castling rights
     pub fn castling_reset(&mut self, w: Whose, cs: Side) -> () {
-        self.castling.reset(w, cs)
+        self.hash ^= self.castling_key();
+        self.castling.reset(w, cs);
+        self.hash ^= self.castling_key();
     }
 
     /// Resets castling rights
     pub fn castling_reset_both(&mut self, w: Whose) -> () {
-        self.castling.reset(w, Side::K);
-        self.castling.reset(w, Side::Q);
+        self.castling_reset(w, Side::K);
+        self.castling_reset(w, Side::Q);
+    }
+
+    /// Helper function, makes `w` castle to side `cs` with its outermost rook
+    /// on that side of the king (as X-FEN's `K`/`Q` mean), or the corner rook
+    /// if there is none
+    /// 
+    /// Must be called before the board is flipped for Black
+    fn castling_find_rook(&mut self, w: Whose, cs: Side) -> () {
+        let king = self.kings[w as usize];
+        let rank = match w {
+            Whose::Ours => Rank::First,
+            Whose::Theirs => Rank::Eighth,
+        };
+        let files = match cs {
+            Side::K => [File::H, File::G, File::F, File::E, File::D, File::C, File::B],
+            Side::Q => [File::A, File::B, File::C, File::D, File::E, File::F, File::G],
+        };
+        let outside_king = |f: File| match cs {
+            Side::K => (f as i8) > (king.file() as i8),
+            Side::Q => (f as i8) < (king.file() as i8),
+        };
+        if king.is_null() || king.rank() != rank {
+            return;
+        }
+        let rook = Piece::Pc(w, PieceType::R);
+        let found = files.iter()
+            .find(|&&f| outside_king(f) && self.get(Square::from(f, rank)) == rook);
+        if let Some(&f) = found {
+            self.castling.set_rook_file(w, cs, f);
+        }
     }
 
     /// Prints the board
@@ -354,102 +1085,67 @@ impl Board {
     /// Our pieces are uppercase, theirs are lowercase
     pub fn print (&self) -> () {
         self.sq_lut.print();
-        println!("");
+        println!();
     }
 
     pub fn board_clear (&mut self) -> () {
         self.whose_bbs = [Bitboard::EMPTY; Whose::COUNT];
         self.piece_type_bbs = [Bitboard::EMPTY; PieceType::NK_COUNT];
         self.kings = [Square::Null; Whose::COUNT];
-        self.sq_lut = SquareLUT::new();
+        self.sq_lut.clear();
         self.castling = Castling::EMPTY; 
         // self.whose =  Whose::Ours;
         self.color = Color::White; 
         self.en_passant = Square::Null; 
-        self.half_moves = 0u8;
+        self.half_moves = 0u16;
         self.rule50 = 0u8;
+        self.hash = 0u64;
+        self.variant = Variant::Standard;
+        self.chess960 = false;
+        self.checks = [0u8; Whose::COUNT];
+        self.pockets = [[0u8; PieceType::NK_COUNT]; Whose::COUNT];
+        self.promoted = Bitboard::EMPTY;
     }
 
     pub fn is_alpha(c : char) -> bool {
-        return c.is_ascii_alphabetic();
+        c.is_ascii_alphabetic()
     }
 
     pub fn is_lower(c: char) -> bool {
-        return c.is_ascii_lowercase();
+        c.is_ascii_lowercase()
     }
 
     pub fn to_lower(c: char) -> char {
-        if (Board::is_lower(c)) {
-            return c;
+        if Board::is_lower(c) {
+            c
         }
         else {
-            return (c as u8 + 32) as char;
+            (c as u8 + 32) as char
         }
     }
 
     pub fn board_from_fen_pieces(&mut self, pieces: &str) -> () {
-        let mut pieces_len = pieces.len();
-
         let mut r = Rank::Eighth;
         let mut f = File::A;
-        // let f = 0;
-        let fen_slice = pieces.as_bytes();
 
-
-        for i in 0..pieces_len {
-            let piece_chr = fen_slice[i] as char;
-            print!("piece_chr: {}\n", piece_chr);
-            let piece = fen_slice[i] as i8;
-            if 49 <= piece && piece <= 56 {
+        for &byte in pieces.as_bytes() {
+            let piece_chr = byte as char;
+            let piece = byte as i8;
+            if (49..=56).contains(&piece) {
                 let open = piece - 48;
-                match open {
-                    1 => f = File::convert(((f as i8) + open) as isize),
-                    2 => f = File::convert(((f as i8) + open) as isize),
-                    3 => f = File::convert(((f as i8) + open) as isize),
-                    4 => f = File::convert(((f as i8) + open) as isize),
-                    5 => f = File::convert(((f as i8) + open) as isize),
-                    6 => f = File::convert(((f as i8) + open) as isize),
-                    8 => f = File::convert(((f as i8) + open) as isize),
-                    _ => panic!("{} is not better ", open)
-                    
-                }
+                f = File::convert(((f as i8) + open) as isize);
                 assert!((f as i8) <= 8);
             }
             else if Board::is_alpha(piece_chr) {
                 let s = Square::from(f,r);
-                // let b = s.to_bitboard();
-                let is_black = Board::is_lower(piece_chr);
-                match piece_chr {
-                    'p' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::P)),
-                    'n' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::N)),
-                    'b' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::B)),
-                    'r' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::R)),
-                    'q' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::Q)),
-                    'k' => Board::set(self,s, Piece::Pc(Whose::Theirs, PieceType::K)),
-
-                    'P' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::P)),
-                    'N' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::N)),
-                    'B' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::B)),
-                    'R' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::R)),
-                    'Q' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::Q)),
-                    'K' => Board::set(self,s, Piece::Pc(Whose::Ours, PieceType::K)),
-
-                    _ => panic!("Alpha character {} is not a piece!", piece_chr)
+                match Piece::from_char(piece_chr) {
+                    Some(p) => Board::set(self, s, p),
+                    None => panic!("Alpha character {} is not a piece!", piece_chr)
                 }
-
-                // if (is_black) {
-
-                // }
-                // else {
-
-                // }
-                // print!("file1: {}\n", (f as i8) + 1i8);
                 f = File::convert(((f as i8) + 1i8 ) as isize);
-                // print!("file: {}\n", f as isize);
                 assert!((f as i8) <= 8);
             }
             else if piece_chr == '/' {
-                // print!("file234: {}\n", (f as i8));
                 assert!((f as i8) == 8);
                 f = File::A;
                 r = Rank::convert(((r as i8) - 1) as isize);
@@ -461,75 +1157,167 @@ impl Board {
     }
 
     pub fn board_from_fen(&mut self, fen: &str) -> () {
-        // let P = Board::new();
         self.board_clear();
-        let fenParts : Vec<_>= fen.split_whitespace().collect();
-        let mut is_black = false;
+        let fen_parts : Vec<_>= fen.split_whitespace().collect();
 
         //Pieces
-        Board::board_from_fen_pieces(self, fenParts[0]);
-        print!("eijfoiwjefjiow\n");
+        Board::board_from_fen_pieces(self, fen_parts[0]);
+
         //Side to move
-        let sideToMove= fenParts[1];
-        if sideToMove.len() != 1 {
-            panic!("Invalid FEN side-to-move {}", sideToMove)
-        }
-        if sideToMove == 'w'.to_string() {
-            is_black = false
-        }
-        else if sideToMove == 'b'.to_string() {
-            is_black = true
-        }
-        else {
-            panic!("Invalid FEN side-to-move char: {}", sideToMove);
-        }
-        print!("1\n");
+        let side_to_move = fen_parts[1];
+        let is_black = match side_to_move {
+            "w" => false,
+            "b" => true,
+            _ => panic!("Invalid FEN side-to-move {}", side_to_move)
+        };
 
         //Castling
-        let castling = fenParts[2];
+        let castling = fen_parts[2];
         let castling_len = castling.len();
-        if !(castling_len == 1 && castling == '-'.to_string()) {
-            if (!(1 <= castling_len && castling_len <= 4)) {
-                panic!("Castling string {} too long", castling);
+        if castling != "-" && !(1..=4).contains(&castling_len) {
+            panic!("Castling string {} too long", castling);
+        }
+        if castling.chars().all(|c| "KQkq-".contains(c)) {
+            // White is ours until the board is flipped for Black below
+            self.castling = Castling::from_fen_field(castling, Color::White);
+            for (w, cs) in self.castling.iter() {
+                self.castling_find_rook(w, cs);
             }
-            let castlingChars: Vec<_>= castling.chars().collect();
-            for c in castlingChars {
-                let castlingElem = c;
-                match castlingElem {
-                    'K' => Board::castling_set(self, Whose::Ours, Side::K),
-                    'Q' => Board::castling_set(self, Whose::Ours, Side::Q),
-                    'k' => Board::castling_set(self, Whose::Theirs, Side::K),
-                    'q' => Board::castling_set(self, Whose::Theirs, Side::Q),   
-                    _ => panic!("Invalid castling char {}", castlingElem)               
+        } else {
+            for c in castling.chars() {
+                let w = if Board::is_lower(c) { Whose::Theirs } else { Whose::Ours };
+                match c.to_ascii_uppercase() {
+                    // Shredder-FEN names the file of the rook
+                    'A'..='H' => {
+                        let f = File::convert((c.to_ascii_uppercase() as u8 - b'A') as isize);
+                        let king = self.kings[w as usize];
+                        let cs = if !king.is_null() && (f as i8) < (king.file() as i8) {
+                            Side::Q
+                        } else {
+                            Side::K
+                        };
+                        self.castling_set(w, cs);
+                        self.castling.set_rook_file(w, cs, f);
+                        self.chess960 = true;
+                    }
+                    // X-FEN mixes in `KQkq` for the outermost rook
+                    'K' | 'Q' => {
+                        let cs = if c.eq_ignore_ascii_case(&'k') { Side::K } else { Side::Q };
+                        self.castling_set(w, cs);
+                        self.castling_find_rook(w, cs);
+                    }
+                    _ => panic!("Invalid castling char {}", c)               
                 }
             }
         }
+        // Castling with the king off the e-file or a rook off the corners
+        // can only be Chess960
+        for w in [Whose::Ours, Whose::Theirs] {
+            let king = self.kings[w as usize];
+            let castles = self.castling_get(w, Side::K) || self.castling_get(w, Side::Q);
+            if castles && !king.is_null() && king.file() != File::E {
+                self.chess960 = true;
+            }
+        }
+        if !self.castling.is_standard() {
+            self.chess960 = true;
+        }
 
         //En passant flag
-        let enPassant = fenParts[3];
-        let mut enPassantFile = File::A;
-        let mut enPassantRank = Rank::First;
-        let mut enPassantChars: Vec<_> = enPassant.chars().collect();
-
-        if enPassant != '-'.to_string() {
-            enPassantFile = File::convert((enPassantChars[0] as i8) as isize);
-            enPassantRank = Rank::convert((enPassantChars[1] as i8) as isize);
+        let en_passant = fen_parts[3];
+        let en_passant_chars: Vec<_> = en_passant.chars().collect();
+        if en_passant != "-" {
+            let en_passant_file = File::convert((en_passant_chars[0] as isize) - ('a' as isize));
+            let en_passant_rank = Rank::convert((en_passant_chars[1] as isize) - ('1' as isize));
+            self.en_passant = Square::from(en_passant_file, en_passant_rank);
         }
 
-
-        //Half moves
-        let halfMove = fenParts[4];
-        self.half_moves = (halfMove.parse::<i32>().unwrap()) as u8;
+        //Half moves (since the last capture or pawn move)
+        let half_move = fen_parts[4];
+        self.rule50 = half_move.parse::<u8>().unwrap();
 
         //Full moves
-        let fullMove = fenParts[5];
-        self.rule50 = fullMove.parse::<i32>().unwrap() as u8;
+        let full_move = fen_parts[5];
+        let full_moves = full_move.parse::<u16>().unwrap();
+        self.half_moves = 2 * (full_moves - 1) + (is_black as u16);
 
         if is_black{
             Board::flip(self);
         }
-        print!("2\n");
+        self.clear_meaningless_en_passant();
+        self.hash = self.zobrist_hash();
+    }
+
+    /// Returns the piece placement field of the board's FEN
+    /// 
+    /// The placement is always written from White's point of view, starting
+    /// at a8, regardless of whose turn it is
+    pub fn fen_placement(&self) -> String {
+        let mut placement = String::new();
+        for (i, row) in PRINT_ORDER.iter().enumerate() {
+            let mut empty = 0u8;
+            for s in row {
+                let sq = match self.color {
+                    Color::White => Square::Sq(*s),
+                    Color::Black => Square::Sq(*s).flipped(),
+                };
+                match self.get(sq) {
+                    Piece::Pc(w, pt) => {
+                        if empty > 0 {
+                            placement.push((b'0' + empty) as char);
+                            empty = 0;
+                        }
+                        let c = Piece::Pc(w, pt).to_char();
+                        match self.color {
+                            Color::White => placement.push(c),
+                            Color::Black => placement.push(Board::swap_case(c)),
+                        }
+                    }
+                    _ => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push((b'0' + empty) as char);
+            }
+            if i < PRINT_ORDER.len() - 1 {
+                placement.push('/');
+            }
+        }
+        placement
+    }
+
+    /// Returns the board as a FEN string
+    pub fn to_fen(&self) -> String {
+        let color = match self.color {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        let white = Color::White.to_whose(self.color);
+        let black = Color::Black.to_whose(self.color);
+        let mut castling = self.castling.to_fen_field(self.color);
+        if self.chess960 && castling != "-" {
+            // Shredder-FEN, e.g. `HAha`
+            castling.clear();
+            for (w, cs) in [(white, Side::K), (white, Side::Q), (black, Side::K), (black, Side::Q)] {
+                if self.castling_get(w, cs) {
+                    let f = (b'A' + self.castling.rook_file(w, cs) as u8) as char;
+                    castling.push(if w == white { f } else { f.to_ascii_lowercase() });
+                }
+            }
+        }
+        let en_passant = match (self.en_passant, self.color) {
+            (Square::Null, _) => String::from("-"),
+            (sq, Color::White) => sq.to_string(),
+            (sq, Color::Black) => sq.flipped().to_string(),
+        };
+        let full_moves = self.half_moves / 2 + 1;
+        format!("{} {} {} {} {} {}", self.fen_placement(), color, castling,
+                en_passant, self.rule50, full_moves)
+    }
 
+    /// Helper function, swaps an ASCII letter between upper and lowercase
+    fn swap_case(c: char) -> char {
+        if Board::is_lower(c) { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() }
     }
 }
 
@@ -539,6 +1327,168 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_make_unmake() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let before = bd;
+        for m in generate_legal(&before) {
+            let undo = bd.make(m);
+            assert_eq!(bd.color(), Color::Black);
+            bd.unmake(undo);
+            assert_eq!(bd, before);
+        }
+    }
+
+    #[test]
+    fn test_make_null_move() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/8/3p4/4K3 w - - 0 1");
+        let before = bd;
+        assert!(!bd.can_make_null_move());
+        assert!(!bd.make_null_move());
+        assert_eq!(bd, before);
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3");
+        assert!(bd.can_make_null_move());
+        assert!(bd.make_null_move());
+        assert_eq!(bd.color(), Color::White);
+        assert_eq!(bd.en_passant(), Square::Null);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+    }
+
+    #[test]
+    fn test_material() {
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(bd.material(Whose::Ours), bd.material(Whose::Theirs));
+        assert_eq!(bd.material(Whose::Ours), 8 * 100 + 2 * 320 + 2 * 330 + 2 * 500 + 900);
+        assert_eq!(bd.piece_count(Piece::Pc(Whose::Theirs, PieceType::P)), 8);
+        assert_eq!(bd.piece_count(Piece::Pc(Whose::Ours, PieceType::K)), 1);
+        assert_eq!(bd.phase(), 24);
+        bd.board_from_fen("4k3/pp6/8/8/8/8/PPP5/RN2K3 w - - 0 1");
+        assert_eq!(bd.material(Whose::Ours) - bd.material(Whose::Theirs), 100 + 500 + 320);
+        assert_eq!(bd.phase(), 3);
+        bd.board_from_fen("4k3/pp6/8/8/8/8/PPP5/4K3 w - - 0 1");
+        assert_eq!(bd.phase(), 0);
+    }
+
+    #[test]
+    fn test_legal_capture_count() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(bd.legal_capture_count(), 0);
+        // Rxa8, dxc5, dxe5, and fxe6 en passant
+        bd.board_from_fen("r3k3/8/3p4/2n1pP2/3P4/8/6b1/R3K3 w - e6 0 1");
+        assert_eq!(bd.legal_capture_count(), 4);
+        let captures = generate_legal(&bd).into_iter().filter(|m| m.capture).count();
+        assert_eq!(bd.legal_capture_count(), captures);
+        // Pinned pieces can't capture
+        bd.board_from_fen("4k3/4r3/8/8/8/3p4/4B3/4K3 w - - 0 1");
+        assert_eq!(bd.legal_capture_count(), 0);
+    }
+
+    #[test]
+    fn test_mobility() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let before = bd;
+        assert_eq!(bd.mobility(Whose::Ours), 20);
+        assert_eq!(bd.mobility(Whose::Theirs), 20);
+        assert_eq!(bd, before);
+        // The bishop and queen open up after 1. e4
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(bd.mobility(Whose::Ours), 20);
+        assert_eq!(bd.mobility(Whose::Theirs), 30);
+    }
+
+    #[test]
+    fn test_captures_ordered() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        // dxc5 wins a knight for a pawn, while Qxe5 loses the queen to dxe5
+        bd.board_from_fen("4k3/8/3p4/2n1p3/3P4/8/4Q3/4K3 w - - 0 1");
+        let color = bd.color();
+        let ucis: Vec<String> = bd.captures_ordered().iter().map(|m| m.to_uci(color)).collect();
+        assert_eq!(ucis.len(), bd.legal_capture_count());
+        let winning = ucis.iter().position(|uci| uci == "d4c5").unwrap();
+        let losing = ucis.iter().position(|uci| uci == "e2e5").unwrap();
+        assert!(winning < losing);
+        assert_eq!(ucis.first().map(String::as_str), Some("d4c5"));
+        assert_eq!(ucis.last().map(String::as_str), Some("e2e5"));
+    }
+
+    #[test]
+    fn test_lazy_eval() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/pp6/8/8/8/8/PPP5/RN2K3 w - - 0 1");
+        assert_eq!(bd.lazy_eval(), eval::evaluate_material(&bd));
+        assert!(bd.lazy_eval() > 0);
+        assert!(eval::evaluate(&bd) > 0);
+        bd.board_from_fen("4k3/pp6/8/8/8/8/PPP5/RN2K3 b - - 0 1");
+        assert_eq!(bd.lazy_eval(), eval::evaluate_material(&bd));
+        assert!(bd.lazy_eval() < 0);
+        assert!(eval::evaluate(&bd) < 0);
+    }
+
+    #[test]
+    fn test_color_whose() {
+        assert_eq!(Whose::Ours.to_color(Color::White), Color::White);
+        assert_eq!(Whose::Theirs.to_color(Color::White), Color::Black);
+        assert_eq!(Whose::Ours.to_color(Color::Black), Color::Black);
+        assert_eq!(Whose::Theirs.to_color(Color::Black), Color::White);
+        assert_eq!(Color::White.to_whose(Color::White), Whose::Ours);
+        assert_eq!(Color::Black.to_whose(Color::White), Whose::Theirs);
+        assert_eq!(Color::White.to_whose(Color::Black), Whose::Theirs);
+        assert_eq!(Color::Black.to_whose(Color::Black), Whose::Ours);
+        for pov in [Color::White, Color::Black] {
+            for w in Whose::all() {
+                assert_eq!(w.to_color(pov).to_whose(pov), w);
+            }
+        }
+    }
+
+    #[test]
+    fn test_all() {
+        assert_eq!(PieceType::all().count(), PieceType::COUNT);
+        assert!(PieceType::all().enumerate().all(|(i, pt)| pt as usize == i));
+        assert_eq!(Whose::all().count(), Whose::COUNT);
+        assert!(Whose::all().enumerate().all(|(i, w)| w as usize == i));
+    }
+
+    #[test]
+    fn test_piecetype() {
+        for (c, pt) in "pnbrqk".chars().zip(PieceType::all()) {
+            assert_eq!(PieceType::from_char(c), Some(pt));
+            assert_eq!(PieceType::from_char(c.to_ascii_uppercase()), Some(pt));
+            assert_eq!(Piece::Pc(Whose::Theirs, pt).to_char(), c);
+        }
+        assert_eq!(PieceType::from_char('x'), None);
+        assert_eq!(PieceType::P.value(), 100);
+        assert_eq!(PieceType::Q.value(), 900);
+        assert_eq!(PieceType::K.value(), 0);
+    }
+
+    #[test]
+    fn test_piece_from_char() {
+        for w in Whose::all() {
+            for pt in PieceType::all() {
+                let p = Piece::Pc(w, pt);
+                assert_eq!(Piece::from_char(p.to_char()), Some(p));
+            }
+        }
+        assert_eq!(Piece::from_char('.'), Some(Piece::Empty));
+        assert_eq!(Piece::from_char('x'), None);
+        assert_eq!(Piece::from_char('1'), None);
+        assert_eq!(Piece::try_from('q'), Ok(Piece::Pc(Whose::Theirs, PieceType::Q)));
+        assert_eq!(Piece::try_from('x'), Err(bits::OutOfRange('x')));
+    }
+
     #[test]
     fn test_board_get_set() {
         let mut bd = Board::new();
@@ -567,7 +1517,7 @@ mod tests {
         print!("hi");
     }
 
-    // #[test]
+    #[test]
     fn test_apply() {
         let mut bd = Board::new();
         bd.clear();
@@ -577,12 +1527,605 @@ mod tests {
         let m = Move {
             to: Square::from(File::E, Rank::Fourth),
             from: Square::from(File::E, Rank::Second),
+            piece: PieceType::P,
             capture: false,
+            captured: None,
             dpp: true,
             promotion: None,
-            castling: None
+            castling: None,
+            drop: None
         };
         bd.apply(m);
         bd.print();
     }
+    #[test]
+    fn test_apply_en_passant() {
+        let mut bd = Board::new();
+        bd.clear();
+        let our_pawn = Square::from(File::E, Rank::Fifth);
+        let their_pawn = Square::from(File::D, Rank::Fifth);
+        bd.set(our_pawn, Piece::Pc(Whose::Ours, PieceType::P));
+        bd.set(their_pawn, Piece::Pc(Whose::Theirs, PieceType::P));
+        bd.en_passant = Square::from(File::D, Rank::Sixth);
+        let m = Move {
+            to: Square::from(File::D, Rank::Sixth),
+            from: our_pawn,
+            piece: PieceType::P,
+            capture: true,
+            captured: Some(PieceType::P),
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        };
+        bd.apply(m);
+        bd.flip();
+        assert_eq!(bd.get(their_pawn), Piece::Empty);
+        assert_eq!(bd.get(our_pawn), Piece::Empty);
+        assert_eq!(bd.get(Square::from(File::D, Rank::Sixth)),
+                   Piece::Pc(Whose::Ours, PieceType::P));
+        assert!(bd.get_pieces(Piece::Pc(Whose::Theirs, PieceType::P)).is_empty());
+    }
+    #[test]
+    fn test_apply_clears_en_passant() {
+        let mut bd = Board::new();
+        bd.clear();
+        bd.set(Square::from(File::E, Rank::Second),
+               Piece::Pc(Whose::Ours, PieceType::P));
+        bd.set(Square::from(File::D, Rank::Fourth),
+               Piece::Pc(Whose::Theirs, PieceType::P));
+        bd.set(Square::from(File::G, Rank::Eighth),
+               Piece::Pc(Whose::Theirs, PieceType::N));
+        bd.apply(Move {
+            to: Square::from(File::E, Rank::Fourth),
+            from: Square::from(File::E, Rank::Second),
+            piece: PieceType::P,
+            capture: false,
+            captured: None,
+            dpp: true,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        // Their d4 pawn could take, so the square is kept for them
+        assert_eq!(bd.en_passant, Square::from(File::E, Rank::Third).flipped());
+        bd.apply(Move {
+            to: Square::from(File::F, Rank::Sixth).flipped(),
+            from: Square::from(File::G, Rank::Eighth).flipped(),
+            piece: PieceType::N,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        assert_eq!(bd.en_passant, Square::Null);
+    }
+    #[test]
+    fn test_fen_placement() {
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(bd.fen_placement(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR");
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(bd.fen_placement(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR");
+    }
+    #[test]
+    fn test_apply_promotion_capture() {
+        let mut bd = Board::new();
+        bd.clear();
+        let h8 = Square::from(File::H, Rank::Eighth);
+        bd.set(Square::from(File::G, Rank::Seventh),
+               Piece::Pc(Whose::Ours, PieceType::P));
+        bd.set(h8, Piece::Pc(Whose::Theirs, PieceType::R));
+        bd.castling_set(Whose::Theirs, Side::K);
+        bd.castling_set(Whose::Theirs, Side::Q);
+        bd.apply(Move {
+            to: h8,
+            from: Square::from(File::G, Rank::Seventh),
+            piece: PieceType::P,
+            capture: true,
+            captured: Some(PieceType::R),
+            dpp: false,
+            promotion: Some(PieceType::Q),
+            castling: None,
+            drop: None
+        });
+        bd.flip();
+        assert_eq!(bd.get(h8), Piece::Pc(Whose::Ours, PieceType::Q));
+        assert_eq!(bd.get_pieces(Piece::Pc(Whose::Ours, PieceType::Q)), h8.to_bitboard());
+        assert!(bd.get_pieces(Piece::Pc(Whose::Theirs, PieceType::R)).is_empty());
+        assert!(bd.get_pieces(Piece::Pc(Whose::Ours, PieceType::P)).is_empty());
+        assert_eq!(bd.get_whose(Whose::Theirs), Bitboard::EMPTY);
+        assert!(!bd.castling_get(Whose::Theirs, Side::K));
+        assert!(bd.castling_get(Whose::Theirs, Side::Q));
+    }
+    #[test]
+    fn test_to_fen() {
+        let mut bd = Board::new();
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b Kq e3 0 3",
+            "4k3/8/8/8/8/8/8/4K2R w K - 12 40",
+        ];
+        for fen in fens {
+            bd.board_from_fen(fen);
+            assert_eq!(bd.to_fen(), fen);
+        }
+    }
+
+    #[cfg(feature = "debug-trace")]
+    #[test]
+    fn test_apply_trace() {
+        use std::cell::RefCell;
+        use std::io::Write;
+        use std::rc::Rc;
+
+        #[derive(Clone)]
+        struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+        impl Write for SharedBuf {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.0.borrow_mut().write(data)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+        }
+
+        let buf = SharedBuf(Rc::new(RefCell::new(Vec::new())));
+        trace::set_sink(Box::new(buf.clone()));
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        bd.apply(Move {
+            to: Square::from(File::E, Rank::Fourth),
+            from: Square::from(File::E, Rank::Second),
+            piece: PieceType::P,
+            capture: false,
+            captured: None,
+            dpp: true,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        bd.apply(Move {
+            to: Square::from(File::F, Rank::Third),
+            from: Square::from(File::G, Rank::First),
+            piece: PieceType::N,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        trace::take_sink();
+        let trace = String::from_utf8(buf.0.borrow().clone()).unwrap();
+        assert_eq!(trace, "\
+e2e4 rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1
+b8c6 r1bqkbnr/pppppppp/2n5/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2
+");
+    }
+    #[test]
+    fn test_apply_castling() {
+        let mut bd = Board::new();
+        bd.board_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        bd.apply(Move {
+            to: Square::from(File::G, Rank::First),
+            from: Square::from(File::E, Rank::First),
+            piece: PieceType::K,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: Some(Side::K),
+            drop: None
+        });
+        // Back to the point of view of the player who castled
+        bd.flip();
+        let rooks = Square::from(File::A, Rank::First).to_bitboard()
+            | Square::from(File::F, Rank::First).to_bitboard();
+        assert_eq!(bd.get_pieces(Piece::Pc(Whose::Ours, PieceType::R)), rooks);
+        assert_eq!(bd.kings[Whose::Ours as usize], Square::from(File::G, Rank::First));
+        assert_eq!(bd.get(Square::from(File::H, Rank::First)), Piece::Empty);
+        assert!(!bd.castling_get(Whose::Ours, Side::K));
+        assert!(!bd.castling_get(Whose::Ours, Side::Q));
+        assert!(bd.castling_get(Whose::Theirs, Side::K));
+        assert!(bd.castling_get(Whose::Theirs, Side::Q));
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // Kingside castling for Black moves the (relative) king to the left
+        bd.board_from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        bd.apply(Move {
+            to: Square::from(File::B, Rank::First),
+            from: Square::from(File::D, Rank::First),
+            piece: PieceType::K,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: Some(Side::K),
+            drop: None
+        });
+        bd.flip();
+        let rooks = Square::from(File::C, Rank::First).to_bitboard()
+            | Square::from(File::H, Rank::First).to_bitboard();
+        assert_eq!(bd.get_pieces(Piece::Pc(Whose::Ours, PieceType::R)), rooks);
+        assert_eq!(bd.kings[Whose::Ours as usize], Square::from(File::B, Rank::First));
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+    }
+
+    #[test]
+    fn test_chess960_castling() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        // The king lands on g1, where the rook started, and the rook on f1
+        bd.board_from_fen("4k3/8/8/8/8/8/8/1R3KR1 w GB - 0 1");
+        assert!(bd.is_chess960());
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/8/8/8/1R3KR1 w GB - 0 1");
+        assert_eq!(bd.castling_rook(Side::K, Whose::Ours), Square::from(File::G, Rank::First));
+        assert_eq!(bd.castling_rook(Side::Q, Whose::Ours), Square::from(File::B, Rank::First));
+        let m = Move::from_uci("f1g1", &bd).unwrap();
+        assert_eq!(m.castling, Some(Side::K));
+        bd.make(m);
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/8/8/8/1R3RK1 b - - 1 1");
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // Queenside, the king goes to c1 and the rook to d1
+        bd.board_from_fen("4k3/8/8/8/8/8/8/1R3KR1 w GB - 0 1");
+        bd.make(Move::from_uci("f1b1", &bd).unwrap());
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/8/8/8/2KR2R1 b - - 1 1");
+        // Moving a castling rook only gives up castling with that rook
+        bd.board_from_fen("4k3/8/8/8/8/8/8/1R3KR1 w GB - 0 1");
+        bd.make(Move::from_uci("g1g2", &bd).unwrap());
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/8/8/6R1/1R3K2 b B - 1 1");
+        // Black castles with the rooks on the files named in the FEN
+        bd.board_from_fen("1r3kr1/8/8/8/8/8/8/4K3 b gb - 0 1");
+        bd.make(Move::from_uci("f8g8", &bd).unwrap());
+        assert_eq!(bd.to_fen(), "1r3rk1/8/8/8/8/8/8/4K3 w - - 1 2");
+        // X-FEN's `K`/`Q` pick the outermost rook on that side of the king
+        bd.board_from_fen("rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1");
+        assert!(bd.is_chess960());
+        assert_eq!(bd.to_fen(), "rk2r3/8/8/8/8/8/8/RK2R3 w EAea - 0 1");
+        // A blocked path rules castling out
+        bd.board_from_fen("4k3/8/8/8/8/8/8/1RN2KR1 w GB - 0 1");
+        assert!(Move::from_uci("f1b1", &bd).is_none());
+        // Standard positions keep standard notation
+        bd.board_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(!bd.is_chess960());
+        assert_eq!(bd.to_fen(), "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+        assert!(Move::from_uci("e1g1", &bd).is_some());
+    }
+
+    #[test]
+    fn test_try_apply() {
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/8/4P3/4KB2 w - - 0 1");
+        let before = bd;
+        let e1 = Square::from(File::E, Rank::First);
+        let f1 = Square::from(File::F, Rank::First);
+        let e2 = Square::from(File::E, Rank::Second);
+        let m = Move { to: e2, from: f1, piece: PieceType::B, capture: false, captured: None,
+                       dpp: false, promotion: None, castling: None, drop: None };
+        assert_eq!(bd.try_apply(m), Err(MoveError::FriendlyCapture(e2)));
+        assert_eq!(bd.try_move_piece(e1, f1), Err(MoveError::FriendlyCapture(e1)));
+        let d3 = Square::from(File::D, Rank::Third);
+        assert_eq!(bd.try_move_piece(e2, d3), Err(MoveError::NoPiece(d3)));
+        assert_eq!(bd, before);
+        let e3 = Square::from(File::E, Rank::Third);
+        let m = Move { to: e3, from: e2, piece: PieceType::P, capture: false, captured: None,
+                       dpp: false, promotion: None, castling: None, drop: None };
+        assert_eq!(bd.try_apply(m), Ok(()));
+        // Now Black is to move, and the board is turned around
+        assert_eq!(bd.get(e3.flipped()), Piece::Pc(Whose::Theirs, PieceType::P));
+        let m = Move { drop: Some(PieceType::N), piece: PieceType::N, from: Square::Null, to: e3.flipped(), ..m };
+        assert_eq!(bd.try_apply(m), Err(MoveError::EmptyPocket(PieceType::N)));
+        bd.pocket_add(Whose::Ours, PieceType::N);
+        assert_eq!(bd.try_apply(m), Err(MoveError::Occupied(e3.flipped())));
+    }
+
+    #[test]
+    fn test_getters() {
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b Kq e3 5 3");
+        assert_eq!(bd.color(), Color::Black);
+        assert_eq!(bd.en_passant(), Square::from(File::E, Rank::Third).flipped());
+        assert_eq!(bd.half_moves(), 5);
+        assert_eq!(bd.rule50(), 5);
+        let castling = bd.castling_rights();
+        assert!(castling.get(Whose::Ours, Side::Q));
+        assert!(castling.get(Whose::Theirs, Side::K));
+        assert!(!castling.get(Whose::Ours, Side::K));
+        assert!(!castling.get(Whose::Theirs, Side::Q));
+    }
+
+    #[test]
+    fn test_swap_sides() {
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/4P3/8/8/R3K3 w Q - 0 1");
+        let before = bd;
+        bd.swap_sides();
+        assert_eq!(bd.color, Color::Black);
+        assert_eq!(bd.get(Square::from(File::E, Rank::Fourth)),
+                   Piece::Pc(Whose::Theirs, PieceType::P));
+        assert_eq!(bd.get(Square::from(File::A, Rank::First)),
+                   Piece::Pc(Whose::Theirs, PieceType::R));
+        assert_eq!(bd.get(Square::from(File::E, Rank::Eighth)),
+                   Piece::Pc(Whose::Ours, PieceType::K));
+        assert_eq!(bd.kings[Whose::Theirs as usize], Square::from(File::E, Rank::First));
+        assert_eq!(bd.get_whose(Whose::Theirs), before.get_whose(Whose::Ours));
+        assert!(bd.castling_get(Whose::Theirs, Side::Q));
+        assert!(!bd.castling_get(Whose::Ours, Side::Q));
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        bd.swap_sides();
+        assert_eq!(bd, before);
+    }
+
+    #[test]
+    fn test_flip_en_passant() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1");
+        let e3 = Square::from(File::E, Rank::Third);
+        bd.apply(Move::from_uci("e2e4", &bd).unwrap());
+        // Black's d4 pawn can take en passant, so the square is kept
+        assert_eq!(bd.en_passant(), e3.flipped());
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1");
+        assert!(generate_legal(&bd).iter().any(|m| m.to_uci(Color::Black) == "d4e3"));
+        // White has no pawn to take with, so flipping back clears it
+        bd.flip();
+        assert_eq!(bd.en_passant(), Square::Null);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // Nor does Black without the d4 pawn
+        bd.board_from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+        bd.apply(Move::from_uci("e2e4", &bd).unwrap());
+        assert_eq!(bd.en_passant(), Square::Null);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        bd.board_from_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1");
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/4P3/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn test_try_get_set() {
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        let e1 = Square::from(File::E, Rank::First);
+        assert_eq!(bd.try_get(Square::Null), None);
+        assert_eq!(bd.try_get(e1), Some(Piece::Pc(Whose::Ours, PieceType::K)));
+        let before = bd;
+        assert_eq!(bd.try_set(Square::Null, Piece::Empty), None);
+        assert_eq!(bd.try_set(e1, Piece::Null), None);
+        assert_eq!(bd, before);
+        let d1 = Square::from(File::D, Rank::First);
+        assert_eq!(bd.try_set(d1, Piece::Pc(Whose::Ours, PieceType::Q)), Some(()));
+        assert_eq!(bd.get(d1), Piece::Pc(Whose::Ours, PieceType::Q));
+    }
+
+    #[test]
+    fn test_make_null() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3");
+        let before = bd;
+        let undo = bd.make_null();
+        assert_eq!(bd.color(), Color::White);
+        assert_eq!(bd.en_passant(), Square::Null);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        assert_ne!(bd.hash(), before.hash());
+        // The position is as if Black had just moved
+        assert_eq!(bd.to_fen(), "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 3");
+        bd.unmake_null(undo);
+        assert_eq!(bd, before);
+        assert_eq!(bd.hash(), before.hash());
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+    }
+
+    #[test]
+    fn test_same_position() {
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3");
+        let mut other = bd;
+        other.rule50 = 7;
+        assert!(bd.same_position(&other));
+        assert_ne!(bd, other);
+        other.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 12 40");
+        assert!(bd.same_position(&other));
+        // Castling rights, en passant, and the side to move all matter
+        for fen in ["rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b Kkq e3 0 3",
+                    "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq - 0 3",
+                    "rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR w KQkq - 0 3"] {
+            other.board_from_fen(fen);
+            assert!(!bd.same_position(&other));
+        }
+    }
+
+    #[test]
+    fn test_hash_map_key() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let mut seen = std::collections::HashMap::new();
+        seen.insert(bd, 42);
+        for m in generate_legal(&bd) {
+            let undo = bd.make(m);
+            assert_eq!(seen.get(&bd), None);
+            bd.unmake(undo);
+            assert_eq!(seen.get(&bd), Some(&42));
+        }
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 5 3");
+        bd.clear();
+        assert_eq!(bd.kings, [Square::Null, Square::Null]);
+        assert_eq!(bd.get_all(), Bitboard::EMPTY);
+        assert_eq!(bd.castling_rights(), Castling::EMPTY);
+        assert_eq!(bd.en_passant(), Square::Null);
+        assert_eq!(bd.half_moves(), 0);
+        assert_eq!(bd.rule50(), 0);
+        assert_eq!(bd.color(), Color::Black);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+    }
+
+    #[test]
+    fn test_is_consistent() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        assert!(!bd.is_consistent());
+        bd.board_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert!(bd.is_consistent());
+        for m in generate_legal(&bd) {
+            let mut after = bd;
+            after.make(m);
+            assert!(after.is_consistent());
+        }
+        // A knight missing from its bitboard
+        let b6 = Square::from(File::B, Rank::Sixth);
+        let mut corrupt = bd;
+        corrupt.piece_type_bbs[PieceType::N as usize].reset(b6);
+        assert!(!corrupt.is_consistent());
+        // A pawn in both sides' bitboards
+        let mut corrupt = bd;
+        corrupt.whose_bbs[Whose::Ours as usize].set(Square::from(File::B, Rank::Fourth));
+        assert!(!corrupt.is_consistent());
+        // A king square that doesn't match
+        let mut corrupt = bd;
+        corrupt.kings[Whose::Theirs as usize] = Square::from(File::D, Rank::Eighth);
+        assert!(!corrupt.is_consistent());
+        // A side without a king
+        bd.board_from_fen("8/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert!(!bd.is_consistent());
+    }
+
+    #[test]
+    fn test_fifty_move_rule() {
+        let mut bd = Board::new();
+        bd.board_from_fen("1n2k3/8/8/8/8/8/4P3/4K1N1 w - - 0 1");
+        // Black's b8 knight is on g1 and c6 on f3 as Black sees the board,
+        // so both knights shuffle back and forth between "g1" and "f3"
+        let g1 = Square::from(File::G, Rank::First);
+        let f3 = Square::from(File::F, Rank::Third);
+        for i in 0..100 {
+            assert!(!bd.is_fifty_move_draw());
+            let (from, to) = if i % 4 < 2 { (g1, f3) } else { (f3, g1) };
+            bd.apply(Move { to, from, piece: PieceType::N, capture: false, captured: None,
+                            dpp: false, promotion: None, castling: None, drop: None });
+        }
+        assert_eq!(bd.rule50(), 100);
+        assert_eq!(bd.half_moves(), 100);
+        assert!(bd.is_fifty_move_draw());
+        bd.apply(Move {
+            to: Square::from(File::E, Rank::Third),
+            from: Square::from(File::E, Rank::Second),
+            piece: PieceType::P,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        assert_eq!(bd.rule50(), 0);
+        assert!(!bd.is_fifty_move_draw());
+    }
+
+    #[test]
+    fn test_apply_counters() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen(STARTPOS);
+        for (uci, half_moves, rule50) in [("e2e4", 1, 0), ("e7e5", 2, 0), ("g1f3", 3, 1),
+                                          ("b8c6", 4, 2), ("f1b5", 5, 3), ("a7a6", 6, 0)] {
+            bd.apply(Move::from_uci(uci, &bd).unwrap());
+            assert_eq!(bd.half_moves(), half_moves, "{}", uci);
+            assert_eq!(bd.rule50(), rule50, "{}", uci);
+        }
+        assert_eq!(bd.color(), Color::White);
+        assert_eq!(bd.to_fen(),
+                   "r1bqkbnr/1ppp1ppp/p1n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 4");
+        bd.apply(Move::from_uci("b5c6", &bd).unwrap());
+        assert_eq!(bd.color(), Color::Black);
+        assert_eq!(bd.to_fen(),
+                   "r1bqkbnr/1ppp1ppp/p1B5/4p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 0 4");
+    }
+
+    #[test]
+    fn test_apply_rook_capture_castling() {
+        let mut bd = Board::new();
+        bd.board_from_fen("r3k2r/8/8/8/8/8/6B1/4K3 w kq - 0 1");
+        bd.apply(Move {
+            to: Square::from(File::H, Rank::Eighth),
+            from: Square::from(File::G, Rank::Second),
+            piece: PieceType::B,
+            capture: true,
+            captured: Some(PieceType::R),
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        // The rights lost are those of the side now to move
+        assert!(!bd.castling_get(Whose::Ours, Side::K));
+        assert!(bd.castling_get(Whose::Ours, Side::Q));
+
+        bd.board_from_fen("r3k2r/6b1/8/8/8/8/8/R3K2R b KQkq - 0 1");
+        bd.apply(Move {
+            to: Square::from(File::H, Rank::First).flipped(),
+            from: Square::from(File::G, Rank::Seventh).flipped(),
+            piece: PieceType::B,
+            capture: true,
+            captured: Some(PieceType::R),
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        });
+        // The rights lost are those of the side now to move
+        assert!(!bd.castling_get(Whose::Ours, Side::K));
+        assert!(bd.castling_get(Whose::Ours, Side::Q));
+    }
+    #[test]
+    fn test_checkmate_stalemate() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1");
+        assert!(bd.is_checkmate());
+        assert!(!bd.is_stalemate());
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 b - - 1 1");
+        assert!(!bd.is_checkmate());
+        assert!(!bd.is_stalemate());
+        bd.board_from_fen("k7/8/1Q6/8/8/8/8/7K b - - 0 1");
+        assert!(bd.is_stalemate());
+        assert!(!bd.is_checkmate());
+    }
+
+    #[test]
+    fn test_dead_position() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        for fen in [
+            "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/4KB2 b - - 0 1",
+            // Bishops all on dark squares
+            "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+            // K+N+N vs K, from either side
+            "4k3/8/8/8/8/8/8/1N2K1N1 w - - 0 1",
+            "1n2k1n1/8/8/8/8/8/8/4K3 w - - 0 1",
+        ] {
+            bd.board_from_fen(fen);
+            assert!(bd.is_dead_position(), "{}", fen);
+        }
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+            // Bishops on opposite colors, and knight against bishop, can mate
+            "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+            "4kb2/8/8/8/8/8/8/1N2K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/1N2KBN1 w - - 0 1",
+            "4k1n1/8/8/8/8/8/8/1N2K1N1 w - - 0 1",
+        ] {
+            bd.board_from_fen(fen);
+            assert!(!bd.is_dead_position(), "{}", fen);
+        }
+    }
 }
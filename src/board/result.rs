@@ -0,0 +1,109 @@
+//! How a game has ended
+
+use std::fmt;
+
+use super::*;
+
+/// The ways a game can be drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DrawReason {
+    /// The side to move has no legal move and isn't in check
+    Stalemate,
+    /// 100 halfmoves without a pawn move or capture
+    FiftyMoveRule,
+    /// The same position for the third time
+    ThreefoldRepetition,
+    /// Neither side can ever checkmate (see [`Board::is_dead_position`])
+    InsufficientMaterial,
+}
+
+/// The result of a finished game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw(DrawReason),
+}
+
+impl GameResult {
+    /// Returns the win for `c`
+    pub fn win(c: Color) -> GameResult {
+        match c {
+            Color::White => GameResult::WhiteWins,
+            Color::Black => GameResult::BlackWins,
+        }
+    }
+}
+
+/// Writes the result as in PGN (e.g. `1-0`)
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::WhiteWins => write!(f, "1-0"),
+            GameResult::BlackWins => write!(f, "0-1"),
+            GameResult::Draw(_) => write!(f, "1/2-1/2"),
+        }
+    }
+}
+
+impl Board {
+    /// Returns the result of the game if it is over on this board, or `None`
+    /// if it goes on
+    ///
+    /// Covers the variant's own win condition (see
+    /// [`variant_winner`](Board::variant_winner)), checkmate, stalemate,
+    /// dead positions, and the fifty-move rule, in that order (so a mate on
+    /// the hundredth halfmove still wins). Threefold repetition needs the
+    /// game's history, so it is left to [`Game::result`](crate::game::Game::result).
+    pub fn result(&self) -> Option<GameResult> {
+        if let Some(c) = self.variant_winner() {
+            return Some(GameResult::win(c));
+        }
+        if generate_legal(self).is_empty() {
+            if self.is_in_check() {
+                let mut winner = self.color;
+                winner.flip();
+                return Some(GameResult::win(winner));
+            }
+            return Some(GameResult::Draw(DrawReason::Stalemate));
+        }
+        if self.is_dead_position() {
+            return Some(GameResult::Draw(DrawReason::InsufficientMaterial));
+        }
+        if self.is_fifty_move_draw() {
+            return Some(GameResult::Draw(DrawReason::FiftyMoveRule));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Helper function, returns the result of a position given as FEN
+    fn result(fen: &str) -> Option<GameResult> {
+        let mut bd = Board::new();
+        bd.board_from_fen(fen);
+        bd.result()
+    }
+
+    #[test]
+    fn test_result() {
+        crate::helper::tables::init();
+        assert_eq!(result(STARTPOS), None);
+        assert_eq!(result("R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1"), Some(GameResult::WhiteWins));
+        assert_eq!(result("6k1/8/8/8/8/8/5PPP/r5K1 w - - 1 1"), Some(GameResult::BlackWins));
+        assert_eq!(result("k7/8/1Q6/8/8/8/8/7K b - - 0 1"),
+                   Some(GameResult::Draw(DrawReason::Stalemate)));
+        assert_eq!(result("4k3/8/8/8/8/8/8/4KB2 w - - 0 1"),
+                   Some(GameResult::Draw(DrawReason::InsufficientMaterial)));
+        assert_eq!(result("4k3/8/8/8/8/8/8/R3K3 w - - 100 80"),
+                   Some(GameResult::Draw(DrawReason::FiftyMoveRule)));
+        // Mate on the hundredth halfmove still counts
+        assert_eq!(result("R5k1/5ppp/8/8/8/8/8/6K1 b - - 100 80"), Some(GameResult::WhiteWins));
+        assert_eq!(GameResult::BlackWins.to_string(), "0-1");
+        assert_eq!(GameResult::Draw(DrawReason::Stalemate).to_string(), "1/2-1/2");
+    }
+}
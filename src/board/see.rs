@@ -0,0 +1,142 @@
+//! Static exchange evaluation (SEE)
+//!
+//! Plays out every capture and recapture on a single square, always with the
+//! least valuable attacker, to estimate what a capture wins or loses without
+//! searching.
+
+use super::*;
+
+/// Value of the king in an exchange, so that it is always captured last
+const SEE_KING_VALUE: i32 = 20_000;
+
+impl Board {
+    /// Returns the material we expect to win (or lose, if negative) by
+    /// making the capture `m` and trading off on its square
+    /// 
+    /// Either side may stop recapturing whenever continuing would lose
+    /// material. Sliders lined up behind the pieces that capture (x-rays)
+    /// join the exchange as the square is cleared for them. A move that
+    /// isn't a capture scores its promotion gain, if any.
+    pub fn see(&self, m: Move) -> i32 {
+        if m.castling.is_some() || m.drop.is_some() {
+            return 0;
+        }
+        let to = m.to;
+        let mut occ = self.get_all();
+        // The swap list: gains[d] is the score of the exchange for the side
+        // that made capture `d` if the exchange stopped right after it
+        let mut gains = [0i32; Square::COUNT];
+        gains[0] = match self.get(to) {
+            Piece::Pc(_, pt) => pt.value(),
+            // En passant is the only capture onto an empty square
+            _ if m.capture => {
+                occ.reset(to.rank_down());
+                PieceType::P.value()
+            }
+            _ => 0,
+        };
+        let mut on_square = see_value(Piece::Pc(Whose::Ours, m.piece));
+        if let Some(pt) = m.promotion {
+            gains[0] += pt.value() - PieceType::P.value();
+            on_square = pt.value();
+        }
+        occ.reset(m.from);
+        let mut side = Whose::Theirs;
+        let mut depth = 0;
+        loop {
+            let attackers = self.attackers_to_through(to, side, occ);
+            let (sq, value) = match self.least_valuable(attackers, side) {
+                Some(attacker) => attacker,
+                None => break,
+            };
+            // The king can't capture into a square the other side still covers
+            let mut other = side;
+            other.flip();
+            if value == SEE_KING_VALUE
+                && !self.attackers_to_through(to, other, occ ^ sq.to_bitboard()).is_empty() {
+                break;
+            }
+            depth += 1;
+            gains[depth] = on_square - gains[depth - 1];
+            on_square = value;
+            occ.reset(sq);
+            side = other;
+        }
+        // Each side only makes a capture if it does better than stopping
+        while depth > 0 {
+            gains[depth - 1] = -(-gains[depth - 1]).max(gains[depth]);
+            depth -= 1;
+        }
+        gains[0]
+    }
+
+    /// Helper function, returns the square and exchange value of the least
+    /// valuable of `w`'s pieces among `attackers`
+    fn least_valuable(&self, attackers: Bitboard, w: Whose) -> Option<(Square, i32)> {
+        PieceType::all().find_map(|pt| {
+            let pieces = attackers & self.get_pieces(Piece::Pc(w, pt));
+            match pieces.lsb() {
+                Square::Null => None,
+                sq => Some((sq, see_value(Piece::Pc(w, pt)))),
+            }
+        })
+    }
+}
+
+/// Helper function, returns the value of a piece in an exchange
+fn see_value(p: Piece) -> i32 {
+    match p {
+        Piece::Pc(_, PieceType::K) => SEE_KING_VALUE,
+        Piece::Pc(_, pt) => pt.value(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::helper::tables;
+
+    /// Helper function, returns the SEE of the legal move `uci`
+    fn see_of(bd: &Board, uci: &str) -> i32 {
+        let m = Move::from_uci(uci, bd).unwrap();
+        bd.see(m)
+    }
+
+    #[test]
+    fn test_see() {
+        tables::init();
+        let mut bd = Board::new();
+        // PxQ, and the pawn is recaptured
+        bd.board_from_fen("4k3/2p5/3q4/4P3/8/8/8/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "e5d6"), 900 - 100);
+        // A hanging queen
+        bd.board_from_fen("4k3/8/3q4/8/8/8/3R4/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "d2d6"), 900);
+        // QxP defended by a pawn loses the queen
+        bd.board_from_fen("4k3/2p5/3p4/8/8/8/3Q4/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "d2d6"), 100 - 900);
+        // QxR would lose the queen to cxd5, so Black doesn't recapture
+        bd.board_from_fen("3qk3/8/8/3p4/2P5/8/3R4/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "d2d5"), 100);
+        // Not a capture
+        assert_eq!(see_of(&bd, "e1e2"), 0);
+    }
+
+    #[test]
+    fn test_see_x_ray() {
+        tables::init();
+        let mut bd = Board::new();
+        // The second rook only joins in once the first one has captured:
+        // RxP RxR RxR wins a pawn and trades rooks
+        bd.board_from_fen("3rk3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1");
+        assert_eq!(see_of(&bd, "d2d5"), 100);
+        // Without the second rook the first is lost for a pawn
+        bd.board_from_fen("3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "d2d5"), 100 - 500);
+        // The queen behind the bishop backs up the capture on the diagonal
+        bd.board_from_fen("4k3/8/5p2/4p3/8/2B5/1Q6/4K3 w - - 0 1");
+        assert_eq!(see_of(&bd, "c3e5"), 100 - 330 + 100);
+    }
+}
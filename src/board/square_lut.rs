@@ -1,6 +1,6 @@
 //! A quick lookup table for determining what [`Piece`] is located at a square
 
-use super::{Piece, Whose, PieceType};
+use super::Piece;
 use super::bits::Square;
 use super::util::PRINT_ORDER;
 
@@ -8,7 +8,7 @@ use super::util::PRINT_ORDER;
 /// 
 /// As opposed to performing bitwise operations on bitboards to figure out 
 /// which piece is where
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
 pub struct SquareLUT {
     data: [Piece; Square::COUNT],
 }
@@ -21,6 +21,11 @@ impl SquareLUT {
         }
     }
 
+    /// Empties every square of the table
+    pub fn clear(&mut self) -> () {
+        self.data = [Piece::Empty; Square::COUNT];
+    }
+
     /// Gets the piece at the given square
     pub fn get(&self, sq: Square) -> Piece {
         match sq {
@@ -37,13 +42,35 @@ impl SquareLUT {
         }
     }
 
+    /// Returns the number of squares holding a piece
+    pub fn count(&self) -> usize {
+        self.data.iter().filter(|p| matches!(p, Piece::Pc(_, _))).count()
+    }
+
+    /// Returns every square holding `p`, from Sq(0) to Sq(63)
+    pub fn find(&self, p: Piece) -> Vec<Square> {
+        self.squares().filter(|&(_, q)| q == p).map(|(sq, _)| sq).collect()
+    }
+
+    /// Returns an iterator over every square along with its piece, from
+    /// Sq(0) to Sq(63)
+    /// 
+    /// Iterating over the table itself only yields the pieces.
+    pub fn squares(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        Square::all().map(|sq| (sq, self.get(sq)))
+    }
+
     // Flips a SquareLUT
     pub fn flip(&mut self) -> () {
         self.data.reverse();
+        self.swap_whose();
+    }
+
+    /// Swaps the owner of every piece, leaving the pieces where they are
+    pub fn swap_whose(&mut self) -> () {
         for p in &mut self.data {
-            match p {
-                Piece::Pc(w, _) => w.flip(),
-                _ => ()
+            if let Piece::Pc(w, _) = p {
+                w.flip();
             }
         }
     }
@@ -105,8 +132,10 @@ impl Iterator for SquareLUTIntoIterator {
 mod tests {
 
     use super::*;
+    use crate::board::{Whose, PieceType};
+    use crate::board::bits::{File, Rank};
 
-    // #[test]
+    #[test]
     fn test_square_lut() {
         let mut sq_lut = SquareLUT::new();
 
@@ -121,4 +150,36 @@ mod tests {
         assert_eq!(sq_lut.get(Square::Sq(38u8)), Piece::Null);
         assert_eq!(sq_lut.get(Square::Sq(63u8)), Piece::Pc(Whose::Theirs, PieceType::Q));
     }
+
+    #[test]
+    fn test_count_find() {
+        let mut sq_lut = SquareLUT::new();
+        assert_eq!(sq_lut.count(), 0);
+        sq_lut.clear();
+        assert_eq!(sq_lut.count(), 0);
+        let rook = Piece::Pc(Whose::Ours, PieceType::R);
+        sq_lut.set(Square::Sq(7u8), rook);
+        sq_lut.set(Square::Sq(0u8), rook);
+        sq_lut.set(Square::Sq(4u8), Piece::Pc(Whose::Ours, PieceType::K));
+        sq_lut.set(Square::Sq(60u8), Piece::Pc(Whose::Theirs, PieceType::K));
+        assert_eq!(sq_lut.count(), 4);
+        assert_eq!(sq_lut.find(rook), vec![Square::Sq(0u8), Square::Sq(7u8)]);
+        assert_eq!(sq_lut.find(Piece::Pc(Whose::Theirs, PieceType::K)), vec![Square::Sq(60u8)]);
+        assert!(sq_lut.find(Piece::Pc(Whose::Theirs, PieceType::Q)).is_empty());
+        assert_eq!(sq_lut.find(Piece::Empty).len(), 60);
+    }
+
+    #[test]
+    fn test_squares() {
+        let mut sq_lut = SquareLUT::new();
+        sq_lut.clear();
+        let e4 = Square::from(File::E, Rank::Fourth);
+        let knight = Piece::Pc(Whose::Ours, PieceType::N);
+        sq_lut.set(e4, knight);
+        let found: Vec<_> = sq_lut.squares().filter(|&(_, p)| p != Piece::Empty).collect();
+        assert_eq!(found, vec![(e4, knight)]);
+        assert_eq!(sq_lut.squares().count(), Square::COUNT);
+        // The piece-only iterator still works, in the same order
+        assert!(sq_lut.into_iter().zip(sq_lut.squares()).all(|(p, (_, q))| p == q));
+    }
 }
\ No newline at end of file
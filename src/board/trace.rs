@@ -0,0 +1,31 @@
+//! Move provenance logging for tracking down corrupted boards
+//! 
+//! Only compiled with the `debug-trace` feature. Once a sink is installed,
+//! every [`Board::apply`](super::Board::apply) on the current thread writes a
+//! line with the move in UCI notation followed by the resulting FEN.
+
+use std::cell::RefCell;
+use std::io::Write;
+
+thread_local! {
+    static SINK: RefCell<Option<Box<dyn Write>>> = RefCell::new(None);
+}
+
+/// Installs the sink that traces on the current thread are written to
+pub fn set_sink(sink: Box<dyn Write>) -> () {
+    SINK.with(|s| *s.borrow_mut() = Some(sink));
+}
+
+/// Removes and returns the current thread's sink, which stops tracing
+pub fn take_sink() -> Option<Box<dyn Write>> {
+    SINK.with(|s| s.borrow_mut().take())
+}
+
+/// Writes a single trace line to the sink (if there is one)
+pub(crate) fn record(uci: &str, fen: &str) -> () {
+    SINK.with(|s| {
+        if let Some(sink) = s.borrow_mut().as_mut() {
+            writeln!(sink, "{} {}", uci, fen).expect("Failed to write move trace");
+        }
+    });
+}
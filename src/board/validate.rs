@@ -0,0 +1,94 @@
+//! Checking that a position could occur in a game
+
+use std::fmt;
+
+use super::*;
+use crate::helper::tables;
+
+/// Reasons a position can be rejected by [`Board::validate`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IllegalPosition {
+    /// A side does not have exactly one king
+    KingCount(Color, usize),
+    /// A pawn stands on the first or eighth rank
+    PawnOnBackRank(Square),
+    /// The side that just moved has left its king in check
+    OpponentInCheck,
+}
+
+impl fmt::Display for IllegalPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IllegalPosition::KingCount(c, n) =>
+                write!(f, "{:?} has {} kings, expected exactly 1", c, n),
+            IllegalPosition::PawnOnBackRank(sq) => write!(f, "pawn on {}", sq),
+            IllegalPosition::OpponentInCheck =>
+                write!(f, "the side not to move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for IllegalPosition {}
+
+impl Board {
+    /// Checks that the position could occur in a game: each side has exactly
+    /// one king, no pawn is on the first or eighth rank, and the side not to
+    /// move isn't in check
+    ///
+    /// Squares in errors are absolute (i.e. from White's point of view).
+    /// The lookup tables must have been built (see
+    /// [`tables::init`](crate::helper::tables::init)).
+    pub fn validate(&self) -> Result<(), IllegalPosition> {
+        for w in Whose::all() {
+            let count = self.sq_lut.find(Piece::Pc(w, PieceType::K)).len();
+            if count != 1 {
+                return Err(IllegalPosition::KingCount(w.to_color(self.color), count));
+            }
+        }
+        let back_ranks = tables::get_rank_bb(Rank::First) | tables::get_rank_bb(Rank::Eighth);
+        let mut pawns = self.piece_type_bbs[PieceType::P as usize] & back_ranks;
+        if let Some(sq) = pawns.pop_lsb() {
+            let sq = match self.color {
+                Color::White => sq,
+                Color::Black => sq.flipped(),
+            };
+            return Err(IllegalPosition::PawnOnBackRank(sq));
+        }
+        if self.is_attacked(self.kings[Whose::Theirs as usize], Whose::Ours) {
+            return Err(IllegalPosition::OpponentInCheck);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Helper function, validates a position given as FEN
+    fn validate(fen: &str) -> Result<(), IllegalPosition> {
+        let mut bd = Board::new();
+        bd.board_from_fen(fen);
+        bd.validate()
+    }
+
+    #[test]
+    fn test_validate() {
+        tables::init();
+        assert_eq!(validate("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), Ok(()));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/8 w - - 0 1"),
+                   Err(IllegalPosition::KingCount(Color::White, 0)));
+        assert_eq!(validate("4k2k/8/8/8/8/8/8/4K3 b - - 0 1"),
+                   Err(IllegalPosition::KingCount(Color::Black, 2)));
+        assert_eq!(validate("4k3/8/8/8/8/8/8/3PK3 w - - 0 1"),
+                   Err(IllegalPosition::PawnOnBackRank(Square::from(File::D, Rank::First))));
+        assert_eq!(validate("3pk3/8/8/8/8/8/8/4K3 b - - 0 1"),
+                   Err(IllegalPosition::PawnOnBackRank(Square::from(File::D, Rank::Eighth))));
+        // White to move with Black in check
+        assert_eq!(validate("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), Ok(()));
+        assert_eq!(validate("R3k3/8/8/8/8/8/8/4K3 w - - 0 1"),
+                   Err(IllegalPosition::OpponentInCheck));
+        assert_eq!(validate("R3k3/8/8/8/8/8/8/4K3 b - - 0 1"), Ok(()));
+    }
+}
@@ -0,0 +1,273 @@
+//! Chess variants that change how a game is won
+//!
+//! Move generation is the same for every variant; only the way a game ends
+//! differs, apart from atomic chess, where captures also blow up the pieces
+//! around them, and crazyhouse, where captured pieces can be dropped back in.
+
+use super::*;
+
+/// The rules a [`Board`] is played under
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Regular chess
+    #[default]
+    Standard,
+    /// Giving check for the third time wins
+    ThreeCheck,
+    /// Bringing your king to one of the four center squares wins
+    KingOfTheHill,
+    /// Captures explode, taking out every piece but pawns next to the
+    /// capture square (and the capturing piece itself); blowing up the
+    /// enemy king wins
+    Atomic,
+    /// Captured pieces go into the capturer's pocket and can be dropped back
+    /// onto the board as a move
+    Crazyhouse,
+}
+
+/// Number of checks that wins a three-check game
+pub const THREE_CHECK_LIMIT: u8 = 3;
+
+impl Board {
+    /// Returns the variant being played
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Sets the variant being played
+    pub fn set_variant(&mut self, variant: Variant) -> () {
+        self.variant = variant;
+    }
+
+    /// Returns the number of times `w` has given check (only counted in
+    /// three-check)
+    pub fn checks_given(&self, w: Whose) -> u8 {
+        self.checks[w as usize]
+    }
+
+    /// Returns the color that has won by the variant's special rule, if any
+    ///
+    /// Checkmate is not a variant rule, so it isn't reported here
+    pub fn variant_winner(&self) -> Option<Color> {
+        let winner = match self.variant {
+            Variant::Standard | Variant::Crazyhouse => None,
+            Variant::ThreeCheck => Whose::all()
+                .find(|&w| self.checks_given(w) >= THREE_CHECK_LIMIT),
+            Variant::KingOfTheHill => {
+                let center = [(File::D, Rank::Fourth), (File::E, Rank::Fourth),
+                              (File::D, Rank::Fifth), (File::E, Rank::Fifth)];
+                Whose::all().find(|&w| {
+                    let king = self.kings[w as usize];
+                    center.iter().any(|&(f, r)| king == Square::from(f, r))
+                })
+            }
+            Variant::Atomic => {
+                match (self.kings[Whose::Ours as usize].is_null(),
+                       self.kings[Whose::Theirs as usize].is_null()) {
+                    (false, true) => Some(Whose::Ours),
+                    (true, false) => Some(Whose::Theirs),
+                    _ => None,
+                }
+            }
+        };
+        winner.map(|w| w.to_color(self.color))
+    }
+
+    /// Returns the number of pieces of type `pt` in the pocket of `w`
+    pub fn pocket(&self, w: Whose, pt: PieceType) -> u8 {
+        match pt {
+            PieceType::K => 0u8,
+            _ => self.pockets[w as usize][pt as usize],
+        }
+    }
+
+    /// Puts a piece of type `pt` into the pocket of `w`
+    pub fn pocket_add(&mut self, w: Whose, pt: PieceType) -> () {
+        let n = self.pocket(w, pt);
+        if pt == PieceType::K || n as usize >= zobrist::POCKET_LIMIT {
+            panic!("Attempted to pocket another {:?} ({} already)", pt, n);
+        }
+        self.hash ^= self.pocket_key(w, pt, n);
+        self.pockets[w as usize][pt as usize] += 1;
+    }
+
+    /// Takes a piece of type `pt` out of the pocket of `w`
+    pub fn pocket_remove(&mut self, w: Whose, pt: PieceType) -> () {
+        let n = self.pocket(w, pt);
+        if n == 0 {
+            panic!("Attempted to take a {:?} out of an empty pocket", pt);
+        }
+        self.pockets[w as usize][pt as usize] -= 1;
+        self.hash ^= self.pocket_key(w, pt, n - 1);
+    }
+
+    /// Helper function, drops a piece of type `pt` from our pocket onto `to`
+    pub(super) fn apply_drop(&mut self, to: Square, pt: PieceType) -> () {
+        if self.get(to) != Piece::Empty {
+            panic!("Attempted to drop a {:?} onto occupied {}", pt, to);
+        }
+        self.pocket_remove(Whose::Ours, pt);
+        self.set(to, Piece::Pc(Whose::Ours, pt));
+    }
+
+    /// Helper function, pockets the piece captured by `m` (promoted pieces
+    /// turn back into pawns) and keeps track of promoted pieces
+    /// 
+    /// Must be called before the move is made on the board
+    pub(super) fn pocket_capture(&mut self, m: Move) -> () {
+        // En passant never captures a promoted piece, since its square is
+        // empty
+        let captured = match m.captured {
+            Some(_) if self.promoted.get(m.to) => Some(PieceType::P),
+            captured => captured,
+        };
+        if let Some(pt) = captured {
+            self.pocket_add(Whose::Ours, pt);
+        }
+        self.promoted.reset(m.to);
+        if m.castling.is_none() && self.promoted.get(m.from) {
+            self.promoted.reset(m.from);
+            self.promoted.set(m.to);
+        }
+        if m.promotion.is_some() {
+            self.promoted.set(m.to);
+        }
+    }
+
+    /// Helper function, blows up the capturing piece on `center` along with
+    /// every piece other than a pawn next to it (for [`Variant::Atomic`])
+    pub(super) fn explode(&mut self, center: Square) -> () {
+        for sq in center.to_bitboard().expand() {
+            let (w, pt) = match self.get(sq) {
+                Piece::Pc(w, pt) if sq == center || pt != PieceType::P => (w, pt),
+                _ => continue,
+            };
+            self.set(sq, Piece::Empty);
+            // Castling with an exploded king or rook is no longer possible
+            match pt {
+                PieceType::K => self.castling_reset_both(w),
+                PieceType::R => {
+                    for side in [Side::K, Side::Q] {
+                        if sq == self.castling_rook(side, w) {
+                            self.castling_reset(w, side);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Helper function, counts a check given by the move we just made
+    pub(super) fn record_check(&mut self) -> () {
+        if self.variant != Variant::ThreeCheck {
+            return;
+        }
+        let king = self.kings[Whose::Theirs as usize];
+        if !king.is_null() && self.is_attacked(king, Whose::Ours) {
+            self.checks[Whose::Ours as usize] += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::helper::tables;
+
+    #[test]
+    fn test_three_check() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        bd.set_variant(Variant::ThreeCheck);
+        for uci in ["a1a8", "e8e7", "a8a7", "e7e6"] {
+            bd.make(Move::from_uci(uci, &bd).unwrap());
+        }
+        assert_eq!(bd.checks_given(Whose::Ours), 2);
+        assert_eq!(bd.checks_given(Whose::Theirs), 0);
+        assert_eq!(bd.variant_winner(), None);
+        bd.make(Move::from_uci("a7a6", &bd).unwrap());
+        assert_eq!(bd.checks_given(Whose::Theirs), 3);
+        assert_eq!(bd.variant_winner(), Some(Color::White));
+        // Checks don't count in standard chess
+        bd.board_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+        bd.make(Move::from_uci("a1a8", &bd).unwrap());
+        assert_eq!(bd.checks_given(Whose::Theirs), 0);
+    }
+
+    #[test]
+    fn test_atomic() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/3pp3/8/8/8/8/8/4R1K1 w - - 0 1");
+        bd.set_variant(Variant::Atomic);
+        bd.make(Move::from_uci("e1e7", &bd).unwrap());
+        // The rook, the pawn it took, and the king next to them are gone
+        let e7 = Square::from(File::E, Rank::Seventh).flipped();
+        let e8 = Square::from(File::E, Rank::Eighth).flipped();
+        let d7 = Square::from(File::D, Rank::Seventh).flipped();
+        assert_eq!(bd.get(e7), Piece::Empty);
+        assert_eq!(bd.get(e8), Piece::Empty);
+        assert_eq!(bd.get(d7), Piece::Pc(Whose::Ours, PieceType::P));
+        assert!(bd.get_pieces(Piece::Pc(Whose::Theirs, PieceType::R)).is_empty());
+        assert_eq!(bd.variant_winner(), Some(Color::White));
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // A king can never capture since it would blow itself up
+        bd.board_from_fen("4k3/8/8/8/8/8/5p2/4K3 w - - 0 1");
+        bd.set_variant(Variant::Atomic);
+        let color = bd.color();
+        assert!(generate_legal(&bd).iter().all(|m| m.to_uci(color) != "e1f2"));
+        // Standard chess has no explosions
+        bd.set_variant(Variant::Standard);
+        assert!(generate_legal(&bd).iter().any(|m| m.to_uci(color) == "e1f2"));
+    }
+
+    #[test]
+    fn test_crazyhouse() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/3n4/8/8/8/3QK3 w - - 0 1");
+        bd.set_variant(Variant::Crazyhouse);
+        bd.make(Move::from_uci("d1d5", &bd).unwrap());
+        // Black is to move, so our pocket is now Black's
+        assert_eq!(bd.pocket(Whose::Theirs, PieceType::N), 1);
+        assert_eq!(bd.pocket(Whose::Ours, PieceType::N), 0);
+        bd.make(Move::from_uci("e8e7", &bd).unwrap());
+        assert!(generate_legal(&bd).iter().any(|m| m.to_uci(Color::White) == "N@f3"));
+        bd.make(Move::from_uci("N@f3", &bd).unwrap());
+        let f3 = Square::from(File::F, Rank::Third).flipped();
+        assert_eq!(bd.get(f3), Piece::Pc(Whose::Theirs, PieceType::N));
+        assert_eq!(bd.pocket(Whose::Theirs, PieceType::N), 0);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // Captured promoted pieces go back into the pocket as pawns
+        bd.board_from_fen("r3k3/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        bd.set_variant(Variant::Crazyhouse);
+        bd.make(Move::from_uci("b7b8q", &bd).unwrap());
+        bd.make(Move::from_uci("a8b8", &bd).unwrap());
+        assert_eq!(bd.pocket(Whose::Theirs, PieceType::P), 1);
+        assert_eq!(bd.pocket(Whose::Theirs, PieceType::Q), 0);
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        // No drops in standard chess
+        bd.board_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        bd.pocket_add(Whose::Ours, PieceType::N);
+        assert!(generate_legal(&bd).iter().all(|m| m.drop.is_none()));
+    }
+
+    #[test]
+    fn test_king_of_the_hill() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/8/3K4/8/8 w - - 0 1");
+        bd.set_variant(Variant::KingOfTheHill);
+        assert_eq!(bd.variant_winner(), None);
+        let mut bd2 = bd;
+        bd.make(Move::from_uci("d3c4", &bd).unwrap());
+        assert_eq!(bd.variant_winner(), None);
+        bd2.make(Move::from_uci("d3e4", &bd2).unwrap());
+        assert_eq!(bd2.variant_winner(), Some(Color::White));
+        bd2.set_variant(Variant::Standard);
+        assert_eq!(bd2.variant_winner(), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Zobrist hashing of positions
+//!
+//! Every (piece, square) pair, castling right, en passant file, crazyhouse
+//! pocket count, and the side to move gets a random 64-bit key, and a
+//! position's hash is the XOR of the keys of everything in it. The keys are
+//! indexed by absolute colors and squares (i.e. from White's point of view),
+//! so flipping the board over to the other player only toggles the side to
+//! move key.
+//!
+//! [`Board`] keeps its hash up to date incrementally as pieces and rights
+//! change; [`Board::zobrist_hash`] recomputes it from scratch.
+
+use super::*;
+
+/// Number of keys for pieces on squares
+const PIECE_KEYS: usize = Whose::COUNT * PieceType::COUNT * Square::COUNT;
+
+/// Offset of the castling keys (one per castling right)
+const CASTLING_OFFSET: usize = PIECE_KEYS;
+
+/// Offset of the en passant keys (one per file)
+const EN_PASSANT_OFFSET: usize = CASTLING_OFFSET + 4;
+
+/// Offset of the pocket keys (one per color, piece type, and count)
+const POCKET_OFFSET: usize = EN_PASSANT_OFFSET + File::COUNT;
+
+/// Largest number of pieces of a single type a pocket can hold
+pub const POCKET_LIMIT: usize = 32;
+
+/// Offset of the side to move key
+const SIDE_OFFSET: usize = POCKET_OFFSET + Whose::COUNT * PieceType::NK_COUNT * POCKET_LIMIT;
+
+/// Total number of keys
+const KEY_COUNT: usize = SIDE_OFFSET + 1;
+
+/// Generates `N` pseudo-random keys with SplitMix64
+const fn generate_keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0u64; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+/// All of the Zobrist keys, computed at compile time
+static KEYS: [u64; KEY_COUNT] = generate_keys(0x5275_7374_4D6F_6E6B);
+
+/// Key toggled whenever it is Black's turn
+pub const SIDE_KEY: u64 = KEYS[SIDE_OFFSET];
+
+impl Board {
+    /// Returns the (incrementally updated) Zobrist hash of the position
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Computes the Zobrist hash of the position from scratch
+    pub fn zobrist_hash(&self) -> u64 {
+        let mut hash = 0u64;
+        for sq in Square::all() {
+            hash ^= self.piece_key(sq, self.get(sq));
+        }
+        hash ^= self.castling_key();
+        hash ^= self.en_passant_key();
+        for w in Whose::all() {
+            for pt in PieceType::all().take(PieceType::NK_COUNT) {
+                for n in 0..self.pocket(w, pt) {
+                    hash ^= self.pocket_key(w, pt, n);
+                }
+            }
+        }
+        if self.color == Color::Black {
+            hash ^= SIDE_KEY;
+        }
+        hash
+    }
+
+    /// Returns the key of piece `p` standing on `sq` (zero for no piece)
+    pub(super) fn piece_key(&self, sq: Square, p: Piece) -> u64 {
+        match p {
+            Piece::Null | Piece::Empty => 0u64,
+            Piece::Pc(w, pt) => {
+                let (color, abs_sq) = match (w, self.color) {
+                    (Whose::Ours, Color::White) => (0, sq),
+                    (Whose::Theirs, Color::White) => (1, sq),
+                    (Whose::Ours, Color::Black) => (1, sq.flipped()),
+                    (Whose::Theirs, Color::Black) => (0, sq.flipped()),
+                };
+                let index = (color * PieceType::COUNT + pt as usize) * Square::COUNT
+                    + abs_sq.val() as usize;
+                KEYS[index]
+            }
+        }
+    }
+
+    /// Returns the combined key of the current castling rights
+    pub(super) fn castling_key(&self) -> u64 {
+        let mut key = 0u64;
+        for (w, cs) in self.castling.iter() {
+            // White's kingside and queenside rights, then Black's
+            let i = 2 * w.to_color(self.color) as usize + cs as usize;
+            key ^= KEYS[CASTLING_OFFSET + i];
+        }
+        key
+    }
+
+    /// Returns the key toggled when the pocket of `w` grows from `n` to
+    /// `n + 1` pieces of type `pt`
+    pub(super) fn pocket_key(&self, w: Whose, pt: PieceType, n: u8) -> u64 {
+        let color = w.to_color(self.color) as usize;
+        let index = (color * PieceType::NK_COUNT + pt as usize) * POCKET_LIMIT + n as usize;
+        KEYS[POCKET_OFFSET + index]
+    }
+
+    /// Returns the key of the en passant file (zero if there is none)
+    pub(super) fn en_passant_key(&self) -> u64 {
+        match (self.en_passant, self.color) {
+            (Square::Null, _) => 0u64,
+            (sq, Color::White) => KEYS[EN_PASSANT_OFFSET + sq.file() as usize],
+            (sq, Color::Black) => KEYS[EN_PASSANT_OFFSET + sq.flipped().file() as usize],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_incremental_hash() {
+        crate::helper::tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(bd.hash(), bd.zobrist_hash());
+        for m in crate::movegen::generate_legal(&bd) {
+            let mut bd2 = bd;
+            bd2.apply(m);
+            assert_eq!(bd2.hash(), bd2.zobrist_hash(), "{}", m.to_uci(Color::White));
+            bd2.flip();
+            assert_eq!(bd2.hash(), bd2.zobrist_hash(), "{}", m.to_uci(Color::White));
+        }
+    }
+
+    #[test]
+    fn test_transposition() {
+        let mut bd1 = Board::new();
+        bd1.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut bd2 = bd1;
+        let nf3 = Move {
+            to: Square::from(File::F, Rank::Third),
+            from: Square::from(File::G, Rank::First),
+            piece: PieceType::N,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        };
+        let nc3 = Move {
+            to: Square::from(File::C, Rank::Third),
+            from: Square::from(File::B, Rank::First),
+            piece: PieceType::N,
+            capture: false,
+            captured: None,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None
+        };
+        // Both sides' knights sit on "b1" and "g1" from their own side, so
+        // 1. Nf3 Nc6 2. Nc3 Nf6 and 1. Nc3 Nf6 2. Nf3 Nc6 transpose
+        bd1.apply(nf3);
+        bd1.apply(nf3);
+        bd2.apply(nc3);
+        bd2.apply(nc3);
+        assert_ne!(bd1.hash(), bd2.hash());
+        bd1.apply(nc3);
+        bd1.apply(nc3);
+        bd2.apply(nf3);
+        bd2.apply(nf3);
+        assert_eq!(bd1.hash(), bd2.hash());
+        let mut bd3 = bd1;
+        bd3.flip();
+        assert_eq!(bd3.hash(), bd1.hash() ^ SIDE_KEY);
+    }
+}
@@ -0,0 +1,248 @@
+//! Polyglot opening books (`.bin` files)
+//!
+//! A book is a list of 16-byte big-endian entries, sorted by key: the
+//! position's 64-bit Polyglot key, the move, its weight, and a learning
+//! value that is ignored here.
+//!
+//! Positions are looked up by their Polyglot key (see [`polyglot_key`]),
+//! not by our own Zobrist hash.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::bits::{File, Rank, Square};
+use crate::board::{Board, Color, Piece, PieceType, Whose};
+use crate::helper::tables;
+use crate::movegen::Move;
+
+mod polyglot;
+
+pub use polyglot::polyglot_key;
+
+/// Size of an entry in bytes
+const ENTRY_SIZE: usize = 16;
+
+/// A single book entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BookEntry {
+    /// Polyglot key of the position
+    pub key: u64,
+    /// The move, packed as Polyglot does (see [`OpeningBook::probe_key`])
+    pub raw_move: u16,
+    /// How often the move should be played relative to the others
+    pub weight: u16,
+}
+
+/// An opening book loaded into memory
+#[derive(Clone, Debug)]
+pub struct OpeningBook {
+    /// Entries sorted by key
+    entries: Vec<BookEntry>,
+    /// State of the generator used to pick weighted moves
+    rng: u64,
+}
+
+impl OpeningBook {
+    /// Loads the book at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<OpeningBook> {
+        OpeningBook::from_bytes(&fs::read(path)?)
+    }
+
+    /// Reads a book from the contents of a `.bin` file
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<OpeningBook> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "book size is not a multiple of 16 bytes"));
+        }
+        let mut entries: Vec<BookEntry> = bytes.chunks_exact(ENTRY_SIZE)
+            .map(|e| BookEntry {
+                key: u64::from_be_bytes([e[0], e[1], e[2], e[3], e[4], e[5], e[6], e[7]]),
+                raw_move: u16::from_be_bytes([e[8], e[9]]),
+                weight: u16::from_be_bytes([e[10], e[11]]),
+            })
+            .collect();
+        // Books should already be sorted, but searching relies on it
+        entries.sort_by_key(|e| e.key);
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        // Xorshift gets stuck on zero
+        Ok(OpeningBook { entries, rng: seed | 1 })
+    }
+
+    /// Returns the number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether or not the book has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns every entry for the position with Polyglot key `key`
+    pub fn entries(&self, key: u64) -> &[BookEntry] {
+        let start = self.entries.partition_point(|e| e.key < key);
+        let end = self.entries.partition_point(|e| e.key <= key);
+        &self.entries[start..end]
+    }
+
+    /// Picks one of the book moves for `board` at random in proportion to
+    /// their weights, or returns `None` if it isn't in the book
+    pub fn probe(&mut self, board: &Board) -> Option<Move> {
+        self.probe_key(polyglot_key(board), board)
+    }
+
+    /// Picks one of the book moves for `board`, whose Polyglot key is `key`,
+    /// at random in proportion to their weights
+    ///
+    /// Moves that aren't legal on `board` (e.g. from a key collision) and
+    /// moves with no weight are never picked.
+    ///
+    /// Polyglot packs a move into 16 bits: the destination file and rank,
+    /// then the origin file and rank (3 bits each, from the lowest), then
+    /// the promotion piece (none, knight, bishop, rook, queen). Castling is
+    /// written as the king capturing its own rook.
+    pub fn probe_key(&mut self, key: u64, board: &Board) -> Option<Move> {
+        let candidates: Vec<(Move, u32)> = self.entries(key).iter()
+            .filter(|e| e.weight > 0)
+            .filter_map(|e| decode_move(e.raw_move, board).map(|m| (m, e.weight as u32)))
+            .collect();
+        let total: u32 = candidates.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut pick = (tables::next_random(&mut self.rng) % total as u64) as u32;
+        for (m, weight) in candidates {
+            if pick < weight {
+                return Some(m);
+            }
+            pick -= weight;
+        }
+        unreachable!()
+    }
+}
+
+/// Helper function, turns a Polyglot move into a legal move on `board`
+fn decode_move(raw: u16, board: &Board) -> Option<Move> {
+    let square = |shift: u16| {
+        let file = File::convert(((raw >> shift) & 7) as isize);
+        let rank = Rank::convert(((raw >> (shift + 3)) & 7) as isize);
+        Square::from(file, rank)
+    };
+    let (from, mut to) = (square(6), square(0));
+    let promotion = match (raw >> 12) & 7 {
+        1 => "n",
+        2 => "b",
+        3 => "r",
+        4 => "q",
+        _ => "",
+    };
+    let relative = |sq: Square| match board.color() {
+        Color::White => sq,
+        Color::Black => sq.flipped(),
+    };
+    let king = Piece::Pc(Whose::Ours, PieceType::K);
+    // Our own moves already write Chess960 castling as the king taking its
+    // rook, and a standard king can't otherwise reach a corner from e1/e8
+    if !board.is_chess960() && board.get(relative(from)) == king
+        && from.file() == File::E && to.rank() == from.rank()
+        && matches!(to.file(), File::A | File::H) {
+        let file = if to.file() == File::H { File::G } else { File::C };
+        to = Square::from(file, to.rank());
+    }
+    Move::from_uci(&format!("{}{}{}", from, to, promotion), board)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Helper function, packs an entry the way a `.bin` file stores it
+    fn entry_bytes(key: u64, raw_move: u16, weight: u16) -> Vec<u8> {
+        let mut bytes = key.to_be_bytes().to_vec();
+        bytes.extend(raw_move.to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(0u32.to_be_bytes());
+        bytes
+    }
+
+    /// Helper function, packs a move given by origin and destination
+    /// (file, rank) pairs
+    fn raw(from: (u16, u16), to: (u16, u16)) -> u16 {
+        to.0 | to.1 << 3 | from.0 << 6 | from.1 << 9
+    }
+
+    #[test]
+    fn test_probe_key() {
+        tables::init();
+        // Polyglot key of the starting position, from the specification
+        let start = 0x463B96181691FC9C;
+        let e2e4 = raw((4, 1), (4, 3));
+        let d2d4 = raw((3, 1), (3, 3));
+        let e1h1 = raw((4, 0), (7, 0));
+        let mut bytes = Vec::new();
+        bytes.extend(entry_bytes(start + 1, e1h1, 1));
+        bytes.extend(entry_bytes(start, e2e4, 3));
+        bytes.extend(entry_bytes(start, d2d4, 0));
+        let mut book = OpeningBook::from_bytes(&bytes).unwrap();
+        assert_eq!(book.len(), 3);
+        assert_eq!(book.entries(start).len(), 2);
+        assert!(book.entries(start - 1).is_empty());
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        // d2d4 has no weight, so e2e4 is always picked
+        for _ in 0..10 {
+            assert_eq!(book.probe_key(start, &bd).map(|m| m.to_uci(bd.color())),
+                       Some(String::from("e2e4")));
+        }
+        assert_eq!(book.probe_key(start - 1, &bd), None);
+        // Castling is written as the king taking its rook, and illegal
+        // moves are skipped
+        assert_eq!(book.probe_key(start + 1, &bd), None);
+        bd.board_from_fen("4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+        assert_eq!(book.probe_key(start + 1, &bd).map(|m| m.castling.is_some()), Some(true));
+        assert!(OpeningBook::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_probe() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let start = 0x463B96181691FC9C;
+        assert_eq!(polyglot_key(&bd), start);
+        let g1f3 = raw((6, 0), (5, 2));
+        let bytes = entry_bytes(start, g1f3, 1);
+        let mut book = OpeningBook::from_bytes(&bytes).unwrap();
+        assert_eq!(book.probe(&bd).map(|m| m.to_uci(bd.color())), Some(String::from("g1f3")));
+        // After 1. Nf3 it's Black's turn, and nothing is in the book
+        bd.play(book.probe(&bd).unwrap());
+        assert_eq!(book.probe(&bd), None);
+    }
+
+    #[test]
+    fn test_decode_castling() {
+        tables::init();
+        let mut bd = Board::new();
+        let e1h1 = raw((4, 0), (7, 0));
+        let e8a8 = raw((4, 7), (0, 7));
+        // Only the king castles; a rook on e1 just moves
+        bd.board_from_fen("4k3/8/8/8/8/8/8/K3R3 w - - 0 1");
+        let m = decode_move(e1h1, &bd);
+        assert_eq!(m.map(|m| (m.piece, m.to_uci(bd.color()))),
+                   Some((PieceType::R, String::from("e1h1"))));
+        bd.board_from_fen("r3k3/8/8/8/8/8/8/4K3 b q - 0 1");
+        assert_eq!(decode_move(e8a8, &bd).map(|m| m.to_uci(bd.color())),
+                   Some(String::from("e8c8")));
+        // Chess960 castling is already written as the king taking its rook
+        bd.board_from_fen("4k3/8/8/8/8/8/8/4K2R w H - 0 1");
+        bd.set_chess960(true);
+        let m = decode_move(e1h1, &bd).unwrap();
+        assert!(m.castling.is_some());
+        assert_eq!(m.to_uci(bd.color()), "e1h1");
+    }
+}
@@ -0,0 +1,192 @@
+//! Static evaluation of chess positions
+//!
+//! Scores are in centipawns and relative to the side to move, so a positive
+//! score is good for [`Whose::Ours`].
+
+use crate::board::{Board, Piece, PieceType, Whose};
+use crate::board::bits::{File, Rank, Square, Bitboard};
+use crate::helper::tables;
+
+mod pawns;
+pub mod pst;
+
+pub use pawns::{doubled_pawns, isolated_pawns, passed_pawns};
+
+/// Penalty for a bishop shut in on a7/h7 by an enemy pawn
+pub const TRAPPED_BISHOP_PENALTY: i32 = 150;
+
+/// Penalty for a knight on the rim without a single safe square
+pub const TRAPPED_KNIGHT_PENALTY: i32 = 100;
+
+/// Evaluates the position
+pub fn evaluate(board: &Board) -> i32 {
+    evaluate_material(board) + piece_placement(board) + king_placement(board)
+        + trapped_pieces(board)
+}
+
+/// Returns the material balance (our material minus theirs)
+pub fn evaluate_material(board: &Board) -> i32 {
+    board.material(Whose::Ours) - board.material(Whose::Theirs)
+}
+
+/// Returns the bonus for where the pieces other than the kings stand (ours
+/// minus theirs), from the piece-square tables
+pub fn piece_placement(board: &Board) -> i32 {
+    let mut score = 0;
+    for (w, sign) in [(Whose::Ours, 1), (Whose::Theirs, -1)] {
+        for pt in PieceType::all().filter(|&pt| pt != PieceType::K) {
+            let table = pst::table(pt);
+            for sq in board.get_pieces(Piece::Pc(w, pt)) {
+                score += sign * table[pst::index(w, sq)];
+            }
+        }
+    }
+    score
+}
+
+/// Returns the bonus for where the kings stand (ours minus theirs)
+///
+/// The middlegame and endgame king tables are blended by the game phase, so
+/// the king goes from hiding to heading for the center as pieces come off
+pub fn king_placement(board: &Board) -> i32 {
+    let phase = board.phase();
+    let mut score = 0;
+    for (w, sign) in [(Whose::Ours, 1), (Whose::Theirs, -1)] {
+        for sq in board.get_pieces(Piece::Pc(w, PieceType::K)) {
+            let i = pst::index(w, sq);
+            score += sign * (pst::KING_MG[i] * phase + pst::KING_EG[i] * (24 - phase)) / 24;
+        }
+    }
+    score
+}
+
+/// Returns the penalty for trapped pieces (ours count against us, theirs
+/// count for us)
+///
+/// Covers two classic patterns:
+/// - a bishop on a7 or h7 whose escape diagonal is blocked by a pawn on b6
+///   or g6 (e.g. after an early Bxa7 ... b6)
+/// - a knight on the edge of the board whose every move lands on a friendly
+///   piece or a square controlled by an enemy pawn
+pub fn trapped_pieces(board: &Board) -> i32 {
+    trapped_pieces_of(board, Whose::Theirs) - trapped_pieces_of(board, Whose::Ours)
+}
+
+/// Helper function, sums the penalties for `w`'s trapped pieces
+fn trapped_pieces_of(board: &Board, w: Whose) -> i32 {
+    let mut them = w;
+    them.flip();
+    let mut penalty = 0;
+
+    // Trapped bishops are the same two patterns for both sides since the
+    // board is rotated (not mirrored) for the opponent
+    let traps = [((File::A, Rank::Seventh), (File::B, Rank::Sixth)),
+                 ((File::H, Rank::Seventh), (File::G, Rank::Sixth))];
+    let bishop = Piece::Pc(w, PieceType::B);
+    let pawn = Piece::Pc(them, PieceType::P);
+    for ((bf, br), (pf, pr)) in traps {
+        let (mut bishop_sq, mut pawn_sq) = (Square::from(bf, br), Square::from(pf, pr));
+        if w == Whose::Theirs {
+            bishop_sq.flip();
+            pawn_sq.flip();
+        }
+        if board.get(bishop_sq) == bishop && board.get(pawn_sq) == pawn {
+            penalty += TRAPPED_BISHOP_PENALTY;
+        }
+    }
+
+    let rim = tables::get_file_bb(File::A) | tables::get_file_bb(File::H)
+        | tables::get_rank_bb(Rank::First) | tables::get_rank_bb(Rank::Eighth);
+    let unsafe_squares = board.get_whose(w) | board.pawn_attacks_by(them);
+    let knights = board.get_pieces(Piece::Pc(w, PieceType::N)) & rim;
+    for sq in knights {
+        let safe: Bitboard = board.attacks_from(sq).without(unsafe_squares);
+        if safe.is_empty() {
+            penalty += TRAPPED_KNIGHT_PENALTY;
+        }
+    }
+    penalty
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_evaluate_material() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(evaluate_material(&bd), 0);
+        assert_eq!(evaluate(&bd), 0);
+        bd.board_from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(evaluate_material(&bd), PieceType::Q.value());
+        bd.board_from_fen("rnb1kbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1");
+        assert_eq!(evaluate_material(&bd), -PieceType::Q.value());
+    }
+
+    #[test]
+    fn test_piece_placement() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/8/8/8/3N4/8/8/4K3 w - - 0 1");
+        let central = evaluate(&bd);
+        assert_eq!(piece_placement(&bd), pst::KNIGHT[27]);
+        bd.board_from_fen("4k3/8/8/8/N7/8/8/4K3 w - - 0 1");
+        let rim = evaluate(&bd);
+        assert!(central > rim);
+        // The same knights for Black, seen from White's side
+        bd.board_from_fen("4k3/8/8/3n4/8/8/8/4K3 w - - 0 1");
+        assert_eq!(evaluate(&bd), -central);
+        bd.board_from_fen("4k3/8/8/n7/8/8/8/4K3 w - - 0 1");
+        assert_eq!(evaluate(&bd), -rim);
+    }
+
+    #[test]
+    fn test_king_placement() {
+        tables::init();
+        let mut bd = Board::new();
+        // In the endgame the centralized king is better
+        bd.board_from_fen("4k3/pppp4/8/8/4K3/8/PPPP4/8 w - - 0 1");
+        let central = king_placement(&bd);
+        bd.board_from_fen("4k3/pppp4/8/8/8/8/PPPP4/6K1 w - - 0 1");
+        let castled = king_placement(&bd);
+        assert!(central > castled);
+        // In the opening the castled king is better
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQ1BNR w kq - 0 1");
+        let central = king_placement(&bd);
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1RK1 w kq - 0 1");
+        let castled = king_placement(&bd);
+        assert!(castled > central);
+        // Seen from Black, the same castled king is just as good
+        bd.board_from_fen("rnbq1rk1/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQ - 0 1");
+        assert_eq!(king_placement(&bd), castled);
+    }
+
+    #[test]
+    fn test_trapped_bishop() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/B1p5/1p6/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(trapped_pieces(&bd), -TRAPPED_BISHOP_PENALTY);
+        bd.board_from_fen("4k3/2p5/1p6/8/8/4B3/8/4K3 w - - 0 1");
+        assert_eq!(trapped_pieces(&bd), 0);
+        // The same trap seen from the side of the player with the bishop
+        bd.board_from_fen("4k3/8/8/8/8/6P1/7b/4K3 b - - 0 1");
+        assert_eq!(trapped_pieces(&bd), -TRAPPED_BISHOP_PENALTY);
+        bd.board_from_fen("4k3/8/8/8/8/6P1/7b/4K3 w - - 0 1");
+        assert_eq!(trapped_pieces(&bd), TRAPPED_BISHOP_PENALTY);
+    }
+
+    #[test]
+    fn test_trapped_knight() {
+        tables::init();
+        let mut bd = Board::new();
+        // Nh1 can only go to f2 (our pawn) or g3 (covered by the h4 pawn)
+        bd.board_from_fen("4k3/8/8/8/7p/8/5P2/4K2N w - - 0 1");
+        assert_eq!(trapped_pieces(&bd), -TRAPPED_KNIGHT_PENALTY);
+        bd.board_from_fen("4k3/8/8/8/N7/8/8/4K3 w - - 0 1");
+        assert_eq!(trapped_pieces(&bd), 0);
+    }
+}
@@ -0,0 +1,104 @@
+//! Pawn structure: doubled, isolated, and passed pawns
+//!
+//! Since the board is relative to the side to move, our pawns always move up
+//! the board and theirs down.
+
+use crate::board::{Board, Color, Piece, PieceType, Whose};
+use crate::board::bits::{Bitboard, Square};
+use crate::helper::{boards, tables};
+
+/// Returns `w`'s pawns that share their file with another of `w`'s pawns
+pub fn doubled_pawns(board: &Board, w: Whose) -> Bitboard {
+    let pawns = board.get_pieces(Piece::Pc(w, PieceType::P));
+    let mut doubled = Bitboard::EMPTY;
+    for sq in pawns {
+        if pawns.count_on_file(sq.file()) > 1 {
+            doubled.set(sq);
+        }
+    }
+    doubled
+}
+
+/// Returns `w`'s pawns without a friendly pawn on either neighbouring file
+pub fn isolated_pawns(board: &Board, w: Whose) -> Bitboard {
+    let pawns = board.get_pieces(Piece::Pc(w, PieceType::P));
+    let mut isolated = Bitboard::EMPTY;
+    for sq in pawns {
+        if pawns.is_disjoint(adjacent_files(sq)) {
+            isolated.set(sq);
+        }
+    }
+    isolated
+}
+
+/// Returns `w`'s pawns that no enemy pawn can stop, i.e. with no enemy pawn
+/// ahead of them on their own file or either neighbouring file
+pub fn passed_pawns(board: &Board, w: Whose) -> Bitboard {
+    let mut them = w;
+    them.flip();
+    let their_pawns = board.get_pieces(Piece::Pc(them, PieceType::P));
+    let mut passed = Bitboard::EMPTY;
+    for sq in board.get_pieces(Piece::Pc(w, PieceType::P)) {
+        if their_pawns.is_disjoint(boards::passed_pawn_mask(sq, pawn_color(w))) {
+            passed.set(sq);
+        }
+    }
+    passed
+}
+
+/// Helper function, returns every square on the files next to `sq`
+fn adjacent_files(sq: Square) -> Bitboard {
+    let mut files = Bitboard::EMPTY;
+    for neighbour in [sq.file_down(), sq.file_up()] {
+        if !neighbour.is_null() {
+            files = files | tables::get_file_bb(neighbour.file());
+        }
+    }
+    files
+}
+
+/// Helper function, returns the color whose pawns move like `w`'s on the
+/// relative board (ours up, like White's, and theirs down)
+fn pawn_color(w: Whose) -> Color {
+    match w {
+        Whose::Ours => Color::White,
+        Whose::Theirs => Color::Black,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::bits::{File, Rank};
+
+    #[test]
+    fn test_doubled_isolated() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("4k3/pp6/8/8/2P5/8/2P2PP1/4K3 w - - 0 1");
+        let c2 = Square::from(File::C, Rank::Second);
+        let c4 = Square::from(File::C, Rank::Fourth);
+        assert_eq!(doubled_pawns(&bd, Whose::Ours), c2.to_bitboard() | c4.to_bitboard());
+        assert_eq!(isolated_pawns(&bd, Whose::Ours), c2.to_bitboard() | c4.to_bitboard());
+        assert!(doubled_pawns(&bd, Whose::Theirs).is_empty());
+        assert!(isolated_pawns(&bd, Whose::Theirs).is_empty());
+    }
+
+    #[test]
+    fn test_passed() {
+        tables::init();
+        let mut bd = Board::new();
+        // Nothing can stop e6 (d6 is beside it, not ahead), but d6 guards
+        // c5's path and h7 g5's
+        bd.board_from_fen("4k3/7p/3pP3/2P3P1/8/8/8/4K3 w - - 0 1");
+        let e6 = Square::from(File::E, Rank::Sixth);
+        assert_eq!(passed_pawns(&bd, Whose::Ours), e6.to_bitboard());
+        // d6 has c5 ahead of it, and h7 has g5
+        assert!(passed_pawns(&bd, Whose::Theirs).is_empty());
+        // The same from Black's side, where the board is turned around
+        bd.board_from_fen("4k3/7p/3pP3/2P3P1/8/8/8/4K3 b - - 0 1");
+        assert_eq!(passed_pawns(&bd, Whose::Theirs), e6.flipped().to_bitboard());
+        assert!(passed_pawns(&bd, Whose::Ours).is_empty());
+    }
+}
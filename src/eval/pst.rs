@@ -0,0 +1,124 @@
+//! Piece-square tables
+//!
+//! Each table holds a bonus (or penalty) in centipawns for a piece standing
+//! on a square. Tables are written from White's point of view and indexed by
+//! [`Square`] value, so the rows run from the first rank up to the eighth.
+
+use crate::board::{PieceType, Whose};
+use crate::board::bits::Square;
+
+/// Pawns: pushed up the board and into the center, but not off the king's
+/// shelter
+#[rustfmt::skip]
+pub const PAWN: [i32; Square::COUNT] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,   5,  10,  25,  25,  10,   5,   5,
+     10,  10,  20,  30,  30,  20,  10,  10,
+     50,  50,  50,  50,  50,  50,  50,  50,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// Knights: strong in the center, weak on the rim
+#[rustfmt::skip]
+pub const KNIGHT: [i32; Square::COUNT] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+/// Bishops: on long, open diagonals and away from the corners
+#[rustfmt::skip]
+pub const BISHOP: [i32; Square::COUNT] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+/// Rooks: on the seventh rank, or centralized on the first
+#[rustfmt::skip]
+pub const ROOK: [i32; Square::COUNT] = [
+      0,   0,   0,   5,   5,   0,   0,   0,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      5,  10,  10,  10,  10,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// Queens: slightly centralized, away from the edges
+#[rustfmt::skip]
+pub const QUEEN: [i32; Square::COUNT] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+/// The king in the middlegame: tucked away behind its pawns
+#[rustfmt::skip]
+pub const KING_MG: [i32; Square::COUNT] = [
+     20,  30,  10,   0,   0,  10,  30,  20,
+     20,  20,   0,   0,   0,   0,  20,  20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+];
+
+/// The king in the endgame: out in the center where it can fight
+#[rustfmt::skip]
+pub const KING_EG: [i32; Square::COUNT] = [
+    -50, -30, -30, -30, -30, -30, -30, -50,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -50, -40, -30, -20, -20, -30, -40, -50,
+];
+
+/// Returns the table for a piece type other than the king (whose table
+/// depends on the game phase, see [`KING_MG`] and [`KING_EG`])
+pub fn table(pt: PieceType) -> &'static [i32; Square::COUNT] {
+    match pt {
+        PieceType::P => &PAWN,
+        PieceType::N => &KNIGHT,
+        PieceType::B => &BISHOP,
+        PieceType::R => &ROOK,
+        PieceType::Q => &QUEEN,
+        PieceType::K => panic!("Attempted to get the king's table without a phase"),
+    }
+}
+
+/// Returns the table index for a piece of `w` on `sq`
+///
+/// The board is always seen from the side to move, so our pieces index the
+/// tables directly and theirs are flipped onto our side of the board
+pub fn index(w: Whose, sq: Square) -> usize {
+    match w {
+        Whose::Ours => sq.val() as usize,
+        Whose::Theirs => sq.flipped().val() as usize,
+    }
+}
@@ -0,0 +1,56 @@
+//! The positions a game has passed through, for detecting repetitions
+
+use crate::board::Board;
+
+/// The hashes of every position reached in a game, oldest first
+#[derive(Clone, Debug, Default)]
+pub struct GameHistory {
+    /// Hash of each position along with its fifty-move rule clock
+    positions: Vec<(u64, u8)>,
+}
+
+impl GameHistory {
+    /// Creates an empty history
+    pub fn new() -> GameHistory {
+        GameHistory { positions: Vec::new() }
+    }
+
+    /// Records that the game reached `board`
+    pub fn push(&mut self, board: &Board) -> () {
+        self.positions.push((board.hash(), board.rule50()));
+    }
+
+    /// Forgets the most recent position
+    pub fn pop(&mut self) -> () {
+        self.positions.pop();
+    }
+
+    /// Returns the number of recorded positions
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns whether or not no positions have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the number of times the position with `hash` has occurred
+    ///
+    /// Only positions since the last irreversible move (a pawn move or a
+    /// capture, which resets the fifty-move clock) are scanned, since no
+    /// position before it can ever come back. A count of 2 is enough for
+    /// search to call a draw, and 3 lets a player claim one.
+    pub fn repetition_count(&self, hash: u64) -> usize {
+        let mut count = 0;
+        for &(h, rule50) in self.positions.iter().rev() {
+            if h == hash {
+                count += 1;
+            }
+            if rule50 == 0 {
+                break;
+            }
+        }
+        count
+    }
+}
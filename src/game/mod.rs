@@ -0,0 +1,184 @@
+//! A game of chess built on top of a [`Board`]
+
+mod history;
+mod pgn;
+
+pub use history::GameHistory;
+pub use pgn::PgnError;
+
+use crate::board::{Board, DrawReason, GameResult, Undo};
+use crate::movegen::{Move, generate_legal};
+
+/// A game in progress
+///
+/// Wraps the current [`Board`] along with the moves played so far and the
+/// [`GameHistory`] of positions they led through, and memoizes its legal
+/// moves, which are regenerated only once the board's hash changes
+#[derive(Clone, Debug)]
+pub struct Game {
+    /// The board the game started from
+    start: Board,
+    board: Board,
+    history: GameHistory,
+    /// Moves played, oldest first
+    moves: Vec<Move>,
+    /// What is needed to take back each move in `moves`
+    undos: Vec<Undo>,
+    /// Legal moves along with the hash of the position they were generated for
+    legal_moves: Option<(u64, Vec<Move>)>,
+}
+
+impl Game {
+    /// Creates a new game starting from `board`
+    pub fn new(board: Board) -> Game {
+        let mut history = GameHistory::new();
+        history.push(&board);
+        Game { start: board, board, history, moves: Vec::new(), undos: Vec::new(), legal_moves: None }
+    }
+
+    /// Returns the current board
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns the positions played so far (including the current one)
+    pub fn history(&self) -> &GameHistory {
+        &self.history
+    }
+
+    /// Returns the current board for modification
+    ///
+    /// The legal move cache notices any change through the board's hash
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    /// Returns the moves played so far, oldest first
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// Plays a (legal) move on the current board and hands the turn to the
+    /// opponent
+    pub fn push_move(&mut self, m: Move) -> () {
+        let undo = self.board.make(m);
+        self.moves.push(m);
+        self.undos.push(undo);
+        self.history.push(&self.board);
+    }
+
+    /// Takes back the last move played, returning it (or `None` if no move
+    /// has been played yet)
+    pub fn undo_move(&mut self) -> Option<Move> {
+        let undo = self.undos.pop()?;
+        self.board.unmake(undo);
+        self.history.pop();
+        self.moves.pop()
+    }
+
+    /// Returns the result of the game if it is over, or `None` if it goes on
+    ///
+    /// Like [`Board::result`], and also a draw once the current position has
+    /// come up for the third time
+    pub fn result(&self) -> Option<GameResult> {
+        let result = self.board.result();
+        if result.is_none() && self.history.repetition_count(self.board.hash()) >= 3 {
+            return Some(GameResult::Draw(DrawReason::ThreefoldRepetition));
+        }
+        result
+    }
+
+    /// Returns the legal moves in the current position
+    ///
+    /// Repeated calls on an unchanged position reuse the previous result
+    pub fn legal_moves(&mut self) -> &[Move] {
+        let hash = self.board.hash();
+        match &self.legal_moves {
+            Some((cached, _)) if *cached == hash => (),
+            _ => self.legal_moves = Some((hash, generate_legal(&self.board))),
+        }
+        match &self.legal_moves {
+            Some((_, moves)) => moves,
+            None => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::helper::tables;
+
+    #[test]
+    fn test_legal_moves_cache() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut game = Game::new(bd);
+        let first = game.legal_moves().to_vec();
+        let first_ptr = game.legal_moves().as_ptr();
+        let second = game.legal_moves().to_vec();
+        // A cache hit hands back the very same list
+        assert_eq!(game.legal_moves().as_ptr(), first_ptr);
+        assert_eq!(first.len(), 20);
+        assert_eq!(first, second);
+        // Changing the board invalidates the cache
+        game.push_move(first[0]);
+        let hash = game.board().hash();
+        game.legal_moves();
+        assert_eq!(game.legal_moves.as_ref().map(|(h, _)| *h), Some(hash));
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let mut game = Game::new(bd);
+        let start = game.board().hash();
+        assert_eq!(game.history().repetition_count(start), 1);
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            game.push_move(Move::from_uci(uci, game.board()).unwrap());
+        }
+        assert_eq!(game.history().repetition_count(start), 2);
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            game.push_move(Move::from_uci(uci, game.board()).unwrap());
+        }
+        assert_eq!(game.history().repetition_count(start), 2);
+        assert_eq!(game.result(), None);
+        game.push_move(Move::from_uci("f6g8", game.board()).unwrap());
+        assert_eq!(game.history().repetition_count(start), 3);
+        assert_eq!(game.result(), Some(GameResult::Draw(DrawReason::ThreefoldRepetition)));
+        // A pawn move makes every earlier position unreachable
+        game.push_move(Move::from_uci("e2e4", game.board()).unwrap());
+        assert_eq!(game.history().repetition_count(start), 0);
+        assert_eq!(game.history().len(), 10);
+    }
+
+    #[test]
+    fn test_push_undo() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let mut game = Game::new(bd);
+        assert_eq!(game.undo_move(), None);
+        let mut played = Vec::new();
+        // Always play the last legal move, which takes in castling and captures
+        for _ in 0..8 {
+            let m = *game.legal_moves().last().unwrap();
+            game.push_move(m);
+            played.push(m);
+        }
+        assert_eq!(game.moves(), &played[..]);
+        assert_eq!(game.history().len(), 9);
+        for m in played.iter().rev() {
+            assert_eq!(game.undo_move(), Some(*m));
+        }
+        assert_eq!(game.board(), &bd);
+        assert_eq!(game.board().hash(), bd.hash());
+        assert!(game.moves().is_empty());
+        assert_eq!(game.history().len(), 1);
+        assert_eq!(game.undo_move(), None);
+    }
+}
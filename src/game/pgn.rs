@@ -0,0 +1,189 @@
+//! Reading and writing games in Portable Game Notation (PGN)
+
+use std::fmt;
+
+use super::Game;
+use crate::board::{Board, Color, STARTPOS};
+use crate::movegen::Move;
+
+/// Reasons PGN can be rejected by [`Game::from_pgn`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PgnError {
+    /// The move at `ply` (counting from 1) is illegal or ambiguous
+    IllegalMove { ply: usize, san: String },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::IllegalMove { ply, san } =>
+                write!(f, "illegal or ambiguous move {} at ply {}", san, ply),
+        }
+    }
+}
+
+impl std::error::Error for PgnError {}
+
+/// Tokens that end the movetext of a game
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+impl Game {
+    /// Returns the game's movetext in PGN, with move numbers and each move in
+    /// SAN (e.g. `1. e4 e5 2. Nf3`), followed by the result (`1-0`, `0-1`,
+    /// `1/2-1/2`, or `*` for a game still in progress)
+    pub fn to_pgn(&self) -> String {
+        let mut board = self.start;
+        let mut tokens = Vec::new();
+        for (i, &m) in self.moves.iter().enumerate() {
+            let number = (self.start.half_moves() as usize + i) / 2 + 1;
+            match board.color() {
+                Color::White => tokens.push(format!("{}.", number)),
+                // A game starting with Black to move needs an ellipsis
+                Color::Black if i == 0 => tokens.push(format!("{}...", number)),
+                Color::Black => (),
+            }
+            tokens.push(m.to_san(&board));
+            board.make(m);
+        }
+        tokens.push(self.result_token());
+        tokens.join(" ")
+    }
+
+    /// Helper function, returns the PGN result token for the current board
+    fn result_token(&self) -> String {
+        match self.result() {
+            Some(result) => result.to_string(),
+            None => String::from("*"),
+        }
+    }
+
+    /// Reads a game from the starting position out of PGN
+    ///
+    /// Tag pairs, comments, variations, and NAGs (e.g. `$1`) are skipped,
+    /// and the movetext ends at the result (or the end of the text)
+    pub fn from_pgn(text: &str) -> Result<Game, PgnError> {
+        let mut board = Board::new();
+        board.board_from_fen(STARTPOS);
+        let mut game = Game::new(board);
+        for token in strip_movetext(text).split_whitespace() {
+            if RESULTS.contains(&token) {
+                break;
+            }
+            // Move numbers may be glued to the move that follows (`1.e4`)
+            let san = match token.rfind('.') {
+                Some(i) => &token[i + 1..],
+                None => token,
+            };
+            if san.is_empty() || san.starts_with('$') {
+                continue;
+            }
+            match Move::from_san(san, game.board()) {
+                Some(m) => game.push_move(m),
+                None => {
+                    let ply = game.moves().len() + 1;
+                    return Err(PgnError::IllegalMove { ply, san: String::from(san) });
+                }
+            }
+        }
+        Ok(game)
+    }
+}
+
+/// Helper function, removes tag pairs, comments, and (possibly nested)
+/// variations from PGN, leaving only the main line's movetext
+fn strip_movetext(text: &str) -> String {
+    let mut movetext = String::new();
+    let mut depth = 0usize;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let end = match c {
+            '[' if depth == 0 => ']',
+            '{' => '}',
+            ';' => '\n',
+            '(' => {
+                depth += 1;
+                continue;
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            _ => {
+                if depth == 0 {
+                    movetext.push(c);
+                }
+                continue;
+            }
+        };
+        chars.by_ref().find(|&c| c == end);
+        movetext.push(' ');
+    }
+    movetext
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::helper::tables;
+
+    #[test]
+    fn test_to_pgn() {
+        tables::init();
+        let mut game = Game::from_pgn("").unwrap();
+        assert_eq!(game.to_pgn(), "*");
+        for uci in ["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"] {
+            game.push_move(Move::from_uci(uci, game.board()).unwrap());
+        }
+        let pgn = game.to_pgn();
+        assert_eq!(pgn, "1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0");
+        // The SAN leads back to the same moves
+        let again = Game::from_pgn(&pgn).unwrap();
+        assert_eq!(again.moves(), game.moves());
+        assert_eq!(again.board(), game.board());
+        // Starting with Black to move
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+        let mut game = Game::new(bd);
+        for uci in ["e7e5", "g1f3"] {
+            game.push_move(Move::from_uci(uci, game.board()).unwrap());
+        }
+        assert_eq!(game.to_pgn(), "1... e5 2. Nf3 *");
+        bd.board_from_fen("7k/8/6Q1/8/8/8/8/K7 b - - 0 1");
+        assert_eq!(Game::new(bd).to_pgn(), "1/2-1/2");
+    }
+
+    #[test]
+    fn test_from_pgn() {
+        tables::init();
+        // Fischer - Spassky, World Championship 1972, game 6
+        let pgn = "[Event \"World Championship\"]
+[Site \"Reykjavik\"]
+[White \"Fischer, Robert James\"]
+[Black \"Spassky, Boris V.\"]
+[Result \"1-0\"]
+
+1. c4 e6 2. Nf3 d5 3. d4 Nf6 4. Nc3 Be7 5. Bg5 O-O 6. e3 h6 7. Bh4 b6
+8. cxd5 Nxd5 9. Bxe7 Qxe7 10. Nxd5 exd5 11. Rc1 Be6 12. Qa4 c5 13. Qa3 Rc8
+14. Bb5 a6 15. dxc5 bxc5 16. O-O Ra7 17. Be2 Nd7 18. Nd4 Qf8 19. Nxe6 fxe6
+20. e4 d4 21. f4 Qe7 22. e5 Rb8 23. Bc4 Kh8 24. Qh3 Nf8 25. b3 a5 26. f5 exf5
+27. Rxf5 Nh7 28. Rcf1 Qd8 29. Qg3 Re7 30. h4 Rbb7 31. e6 Rbc7 32. Qe5 Qe8
+33. a4 Qd8 34. R1f2 Qe8 35. R2f3 Qd8 36. Bd3 Qe8 37. Qe4 Nf6 38. Rxf6! gxf6
+39. Rxf6 Kg8 40. Bc4 Kh8 41. Qf4 1-0";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(game.moves().len(), 81);
+        assert_eq!(game.board().to_fen(),
+                   "4q2k/2r1r3/4PR1p/p1p5/P1Bp1Q1P/1P6/6P1/6K1 b - - 4 41");
+        // Comments, variations, NAGs, and glued move numbers are skipped
+        let pgn = "1.e4 {best by test} e5 (1... c5 2. Nf3 (2. c3) d6) 2. Nf3 $1 ; a comment
+                   2... Nc6 *";
+        let game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(game.to_pgn(), "1. e4 e5 2. Nf3 Nc6 *");
+        // Illegal and ambiguous moves
+        assert_eq!(Game::from_pgn("1. e4 e5 2. Ke3").unwrap_err(),
+                   PgnError::IllegalMove { ply: 3, san: String::from("Ke3") });
+        assert_eq!(Game::from_pgn("1. d4 d5 2. Nf3 Nf6 3. Nd2").unwrap_err(),
+                   PgnError::IllegalMove { ply: 5, san: String::from("Nd2") });
+        assert!(Game::from_pgn("1. d4 d5 2. Nf3 Nf6 3. Nbd2").is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+//! Bitboard masks for evaluation (pawn structure and king safety)
+//! 
+//! The pawn masks are built along with the move generation tables by
+//! [`tables::build`](super::tables::build), from the file and ray tables
+
+use std::ptr::addr_of_mut;
+
+use super::{Direction, tables};
+use crate::board::Color;
+use crate::board::bits::{Square, Bitboard};
+
+/// Lookup table for the squares ahead of a pawn on its own file, indexed by
+/// color and then square
+static mut FRONT_SPAN_TABLE: [[Bitboard; 64]; 2] = [[Bitboard::Null; 64]; 2];
+
+/// Lookup table for the squares ahead of a pawn on its own file and the two
+/// files next to it, indexed by color and then square
+static mut PASSED_PAWN_TABLE: [[Bitboard; 64]; 2] = [[Bitboard::Null; 64]; 2];
+
+/// Builds the front span and passed pawn tables
+/// 
+/// The file and ray tables must already have been built
+pub(super) fn build() -> () {
+    for c in [Color::White, Color::Black] {
+        let dir = match c {
+            Color::White => Direction::North,
+            Color::Black => Direction::South,
+        };
+        for sq in Square::all() {
+            let span = tables::get_ray(sq, dir);
+            let mut mask = span;
+            for neighbour in [sq.file_down(), sq.file_up()] {
+                if !neighbour.is_null() {
+                    mask = mask | tables::get_ray(neighbour, dir);
+                }
+            }
+            unsafe {
+                (*addr_of_mut!(FRONT_SPAN_TABLE))[c as usize][sq.val() as usize] = span;
+                (*addr_of_mut!(PASSED_PAWN_TABLE))[c as usize][sq.val() as usize] = mask;
+            }
+        }
+    }
+}
+
+/// Returns every square ahead of a `c` pawn on `sq`, on the same file
+/// 
+/// White pawns move up the board and Black pawns down. Since a
+/// [`Board`](crate::board::Board) is turned around for Black, our pawns
+/// always move like White's and theirs like Black's.
+pub fn front_span(sq: Square, c: Color) -> Bitboard {
+    match sq {
+        Square::Null => panic!("Attempted to get the front span of Square::Null"),
+        Square::Sq(s) => unsafe { (*addr_of_mut!(FRONT_SPAN_TABLE))[c as usize][s as usize] },
+    }
+}
+
+/// Returns every square ahead of a `c` pawn on `sq`, on the same file or
+/// either neighbouring file
+/// 
+/// The pawn is passed if none of the enemy pawns stand on these squares
+pub fn passed_pawn_mask(sq: Square, c: Color) -> Bitboard {
+    match sq {
+        Square::Null => panic!("Attempted to get the passed pawn mask of Square::Null"),
+        Square::Sq(s) => unsafe { (*addr_of_mut!(PASSED_PAWN_TABLE))[c as usize][s as usize] },
+    }
+}
+
+/// Returns the king zone of a king on `sq`: its own square plus every
+/// square next to it
+/// 
+/// Fewer squares near the edge, e.g. a king on a1 has a ring of a1, a2, b1,
+/// and b2. The lookup tables must have been built.
+pub fn king_ring(sq: Square) -> Bitboard {
+    tables::get_king_moves(sq) | sq.to_bitboard()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::bits::{File, Rank};
+
+    #[test]
+    fn test_front_span() {
+        tables::init();
+        let e4 = Square::from(File::E, Rank::Fourth);
+        assert_eq!(front_span(e4, Color::White).pop_count(), 4);
+        assert_eq!(front_span(e4, Color::Black).pop_count(), 3);
+        assert!(front_span(e4, Color::White).get(Square::from(File::E, Rank::Eighth)));
+        assert!(front_span(e4, Color::Black).get(Square::from(File::E, Rank::First)));
+        assert!(front_span(Square::from(File::A, Rank::Eighth), Color::White).is_empty());
+    }
+
+    #[test]
+    fn test_passed_pawn_mask() {
+        tables::init();
+        let e4 = Square::from(File::E, Rank::Fourth);
+        assert_eq!(passed_pawn_mask(e4, Color::White).pop_count(), 12);
+        assert!(passed_pawn_mask(e4, Color::White).get(Square::from(File::D, Rank::Fifth)));
+        assert!(!passed_pawn_mask(e4, Color::White).get(Square::from(File::D, Rank::Fourth)));
+        // Edge files only have one neighbour
+        let a2 = Square::from(File::A, Rank::Second);
+        assert_eq!(passed_pawn_mask(a2, Color::White).pop_count(), 12);
+        assert_eq!(passed_pawn_mask(a2, Color::Black).pop_count(), 2);
+    }
+
+    #[test]
+    fn test_king_ring() {
+        tables::init();
+        // The corner king plus its three neighbours
+        let a1 = Square::from(File::A, Rank::First);
+        assert_eq!(king_ring(a1).pop_count(), 4);
+        assert!(king_ring(a1).get(a1));
+        assert!(king_ring(a1).get(Square::from(File::B, Rank::Second)));
+        assert_eq!(king_ring(Square::from(File::E, Rank::First)).pop_count(), 6);
+        assert_eq!(king_ring(Square::from(File::E, Rank::Fourth)).pop_count(), 9);
+    }
+}
@@ -1,8 +1,13 @@
 //! An assortment of helper functions and constants
 
+pub mod boards;
 pub mod tables;
 
+use crate::board::{Color, Side, Whose};
+use crate::board::bits::{File, Rank, Square};
+
 /// Movement directions on a chess board
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     North,
     East,
@@ -12,4 +17,117 @@ pub enum Direction {
     Southeast,
     Southwest,
     Northwest,
-}
\ No newline at end of file
+}
+
+impl Direction {
+    /// Returns an iterator over all eight directions (in index order)
+    pub fn all() -> impl Iterator<Item = Direction> {
+        [Direction::North, Direction::East, Direction::South, Direction::West,
+         Direction::Northeast, Direction::Southeast, Direction::Southwest,
+         Direction::Northwest].into_iter()
+    }
+
+    /// Returns the direction pointing the other way
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::Northeast => Direction::Southwest,
+            Direction::Southeast => Direction::Northwest,
+            Direction::Southwest => Direction::Northeast,
+            Direction::Northwest => Direction::Southeast,
+        }
+    }
+}
+
+/// Returns the square `w`'s king starts on in standard chess, relative to
+/// the side to move
+///
+/// `c` is the color of the side to move, which decides both whose back rank
+/// `w` is and how the board is turned (for Black every square is rotated
+/// 180 degrees, so Black's king on e8 is at d1 as Black sees the board).
+/// The king starts on the same square for both sides `_cs` of the board.
+pub fn king_origin(_cs: Side, w: Whose, c: Color) -> Square {
+    back_rank_square(File::E, w, c)
+}
+
+/// Returns the square `w`'s rook for castling on side `cs` starts on in
+/// standard chess, relative to the side to move (see [`king_origin`] for how
+/// `c` is used)
+pub fn rook_origin(cs: Side, w: Whose, c: Color) -> Square {
+    match cs {
+        Side::K => back_rank_square(File::H, w, c),
+        Side::Q => back_rank_square(File::A, w, c),
+    }
+}
+
+/// Maps a file on `w`'s back rank to a square relative to the side to move
+/// (see [`king_origin`] for how `c` is used)
+pub fn back_rank_square(f: File, w: Whose, c: Color) -> Square {
+    let rank = match (w, c) {
+        (Whose::Ours, Color::White) | (Whose::Theirs, Color::Black) => Rank::First,
+        (Whose::Ours, Color::Black) | (Whose::Theirs, Color::White) => Rank::Eighth,
+    };
+    let sq = Square::from(f, rank);
+    match c {
+        Color::White => sq,
+        Color::Black => sq.flipped(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Helper function, names a square given from White's point of view as
+    /// seen by the side to move
+    fn relative(sq: &str, c: Color) -> Square {
+        let chars: Vec<char> = sq.chars().collect();
+        let f = File::try_from(chars[0]).unwrap();
+        let r = Rank::try_from(chars[1]).unwrap();
+        let sq = Square::from(f, r);
+        match c {
+            Color::White => sq,
+            Color::Black => sq.flipped(),
+        }
+    }
+
+    #[test]
+    fn test_origins() {
+        // (whose, side to move, king, kingside rook, queenside rook)
+        let cases = [
+            (Whose::Ours, Color::White, "e1", "h1", "a1"),
+            (Whose::Theirs, Color::White, "e8", "h8", "a8"),
+            (Whose::Ours, Color::Black, "e8", "h8", "a8"),
+            (Whose::Theirs, Color::Black, "e1", "h1", "a1"),
+        ];
+        for (w, c, king, k_rook, q_rook) in cases {
+            for cs in [Side::K, Side::Q] {
+                assert_eq!(king_origin(cs, w, c), relative(king, c));
+            }
+            assert_eq!(rook_origin(Side::K, w, c), relative(k_rook, c));
+            assert_eq!(rook_origin(Side::Q, w, c), relative(q_rook, c));
+        }
+        // Our own back rank is always the first rank as we see it
+        for c in [Color::White, Color::Black] {
+            assert_eq!(king_origin(Side::K, Whose::Ours, c).rank(), Rank::First);
+            assert_eq!(rook_origin(Side::Q, Whose::Theirs, c).rank(), Rank::Eighth);
+        }
+    }
+
+    #[test]
+    fn test_origins_revoke_castling() {
+        tables::init();
+        let mut bd = crate::board::Board::new();
+        // Black takes White's kingside rook and moves its own queenside rook
+        bd.board_from_fen("r3k3/8/8/8/8/6n1/8/R3K2R b KQq - 0 1");
+        bd.make(crate::movegen::Move::from_uci("g3h1", &bd).unwrap());
+        assert_eq!(bd.to_fen(), "r3k3/8/8/8/8/8/8/R3K2n w Qq - 0 2");
+        bd.make(crate::movegen::Move::from_uci("a1a2", &bd).unwrap());
+        bd.make(crate::movegen::Move::from_uci("a8a7", &bd).unwrap());
+        assert_eq!(bd.to_fen(), "4k3/r7/8/8/8/8/R7/4K2n w - - 2 3");
+    }
+}
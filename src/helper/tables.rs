@@ -2,71 +2,131 @@
 //! 
 //! Provides functions for building tables and looking up bitboards
 
+use std::ptr::addr_of_mut;
+use std::sync::Once;
+
 use super::Direction;
 use crate::board::bits::{File, Rank, Square, Bitboard};
 
-/// Lookup table for bitboards with files set
-static mut FILES_TABLE: [Bitboard; 8] = [Bitboard::Null; 8];
-
-/// Lookup table for bitboards with ranks set
-static mut RANKS_TABLE: [Bitboard; 8] = [Bitboard::Null; 8];
-
 /// Lookup table for pawn moves
 static mut PAWN_MOVES_TABLE: [Bitboard; 64] = [Bitboard::Null; 64];
 
 /// Lookup table for pawn attacks
 static mut PAWN_ATTACKS_TABLE: [Bitboard; 64] = [Bitboard::Null; 64];
 
-/// Lookup table for knight moves
-static mut KNIGHT_TABLE: [Bitboard; 64] = [Bitboard::Null; 64];
+/// Lookup table for knight moves, built at compile time
+static KNIGHT_TABLE: [Bitboard; 64] = build_offset_table(&KNIGHT_OFFSETS);
 
-/// Lookup table for king moves
-static mut KING_TABLE: [Bitboard; 64] = [Bitboard::Null; 64];
+/// Lookup table for king moves, built at compile time
+static KING_TABLE: [Bitboard; 64] = build_offset_table(&KING_OFFSETS);
 
 /// Lookup table for rays (rook/bishop/queen moves)
 static mut RAYS_TABLE: [[Bitboard; 64]; 8] = [[Bitboard::Null; 64]; 8];
 
+/// Number of entries in the rook magic attack table (the sum over all
+/// squares of 2 to the number of relevant occupancy bits)
+const ROOK_MAGIC_TABLE_SIZE: usize = 102_400;
+
+/// Number of entries in the bishop magic attack table
+const BISHOP_MAGIC_TABLE_SIZE: usize = 5_248;
+
+/// Magic numbers for rook attacks, one per square
+static mut ROOK_MAGICS: [Magic; 64] = [Magic::EMPTY; 64];
+
+/// Magic numbers for bishop attacks, one per square
+static mut BISHOP_MAGICS: [Magic; 64] = [Magic::EMPTY; 64];
+
+/// Lookup table for rook attacks, indexed through [`ROOK_MAGICS`]
+static mut ROOK_MAGIC_TABLE: [Bitboard; ROOK_MAGIC_TABLE_SIZE] =
+    [Bitboard::Null; ROOK_MAGIC_TABLE_SIZE];
+
+/// Lookup table for bishop attacks, indexed through [`BISHOP_MAGICS`]
+static mut BISHOP_MAGIC_TABLE: [Bitboard; BISHOP_MAGIC_TABLE_SIZE] =
+    [Bitboard::Null; BISHOP_MAGIC_TABLE_SIZE];
+
+/// Seed for the search for magic numbers, so every build finds the same ones
+const MAGIC_SEED: u64 = 0x6D61_6769_6373_2121;
+
+/// A magic multiplier for one square, which hashes every relevant occupancy
+/// of the square into its own slot of an attack table
+/// 
+/// Slots are `offset + ((occ & mask) * magic) >> shift`
+#[derive(Clone, Copy, Debug)]
+struct Magic {
+    /// The squares whose occupancy matters (the rays, without the edges)
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    /// Where this square's slots start in the attack table
+    offset: usize,
+}
+
+impl Magic {
+    const EMPTY: Magic = Magic { mask: 0u64, magic: 0u64, shift: 64u32, offset: 0usize };
+
+    /// Returns the attack table slot for the occupancy `occ`
+    fn index(&self, occ: u64) -> usize {
+        self.offset + ((occ & self.mask).wrapping_mul(self.magic) >> self.shift) as usize
+    }
+}
+
+/// Lookup table for the squares strictly between two squares on a line
+static mut BETWEEN_TABLE: [[Bitboard; 64]; 64] = [[Bitboard::Null; 64]; 64];
+
+/// Lookup table for the whole line (edge to edge) through two squares
+static mut LINE_TABLE: [[Bitboard; 64]; 64] = [[Bitboard::Null; 64]; 64];
+
 /// File-rank offsets for knight move generation
-static KNIGHT_OFFSETS: [(i8, i8); 8] = [
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
     (1,2), (2,1), (2,-1), (1,-2), (-1,-2), (-2,-1), (-2,1), (-1,2)
 ];
 
 /// File-rank offsets for king move generation
-static KING_OFFSETS: [(i8, i8); 8] = [
+const KING_OFFSETS: [(i8, i8); 8] = [
     (0,1), (1,1), (1,0), (1,-1), (0,-1), (-1,-1), (-1,0), (-1,1)
 ];
 
-/// File-rank vectors for rook/bishop/queen move generation
-static RAY_VECTORS: [(i8, i8); 8] = [
-    (0,1), (1,0), (0,-1), (-1,0), (1,1), (1,-1), (-1,-1), (-1,1)
-];
+/// Guards the lookup tables from being built more than once
+static INIT: Once = Once::new();
+
+/// Builds all the lookup tables, unless they have already been built
+/// 
+/// Must be called before any of the getters below. Safe to call from
+/// multiple threads.
+pub fn init() -> () {
+    INIT.call_once(build);
+}
 
 /// Builds all the lookup tables
 pub fn build() -> () {
-    build_files();
-    build_ranks();
     build_pawn_moves();
     build_pawn_attacks();
-    build_knight_moves();
-    build_king_moves();
     build_rays();
+    build_lines();
+    super::boards::build();
+    unsafe {
+        build_magics(&mut *addr_of_mut!(ROOK_MAGICS), &mut *addr_of_mut!(ROOK_MAGIC_TABLE),
+                     get_rook_attacks);
+        build_magics(&mut *addr_of_mut!(BISHOP_MAGICS), &mut *addr_of_mut!(BISHOP_MAGIC_TABLE),
+                     get_bishop_attacks);
+    }
 }
 
+/// Returns the squares on file `f` (see [`Bitboard::FILES`], which needs
+/// no tables)
 pub fn get_file_bb(f: File) -> Bitboard {
-    unsafe {
-        match f {
-            File::Null => panic!("Attempted to get file bitboard from File::Null"),
-            _ => FILES_TABLE[f as usize]
-        }
+    match f {
+        File::Null => panic!("Attempted to get file bitboard from File::Null"),
+        _ => Bitboard::FILES[f as usize]
     }
 }
 
+/// Returns the squares on rank `r` (see [`Bitboard::RANKS`], which needs
+/// no tables)
 pub fn get_rank_bb(r: Rank) -> Bitboard {
-    unsafe {
-        match r {
-            Rank::Null => panic!("Attempted to get rank bitboard from Rank::Null"),
-            _ => RANKS_TABLE[r as usize]
-        }
+    match r {
+        Rank::Null => panic!("Attempted to get rank bitboard from Rank::Null"),
+        _ => Bitboard::RANKS[r as usize]
     }
 }
 
@@ -89,20 +149,16 @@ pub fn get_pawn_attacks(sq: Square) -> Bitboard {
 }
 
 pub fn get_knight_moves(sq: Square) -> Bitboard {
-    unsafe {
-        match sq {
-            Square::Null => panic!("Attempted to get knight moves from Square::Null"),
-            Square::Sq(s) => KNIGHT_TABLE[s as usize]
-        }
+    match sq {
+        Square::Null => panic!("Attempted to get knight moves from Square::Null"),
+        Square::Sq(s) => KNIGHT_TABLE[s as usize]
     }
 }
 
 pub fn get_king_moves(sq: Square) -> Bitboard {
-    unsafe {
-        match sq {
-            Square::Null => panic!("Attempted to get king moves from Square::Null"),
-            Square::Sq(s) => KING_TABLE[s as usize]
-        }
+    match sq {
+        Square::Null => panic!("Attempted to get king moves from Square::Null"),
+        Square::Sq(s) => KING_TABLE[s as usize]
     }
 }
 
@@ -115,44 +171,127 @@ pub fn get_ray(sq: Square, dir: Direction) -> Bitboard {
     }
 }
 
-fn build_files() -> () {
+/// Returns the squares attacked by a slider on `sq` moving along `dir`
+/// 
+/// The ray stops at (and includes) the first square occupied in `occ`
+pub fn get_ray_attacks(sq: Square, dir: Direction, occ: Bitboard) -> Bitboard {
+    let ray = get_ray(sq, dir);
+    let blockers = ray & occ;
+    if blockers.is_empty() {
+        return ray;
+    }
+    let blocker = match dir {
+        Direction::North | Direction::East |
+        Direction::Northeast | Direction::Northwest => blockers.lsb(),
+        Direction::South | Direction::West |
+        Direction::Southeast | Direction::Southwest => blockers.msb(),
+    };
+    ray ^ get_ray(blocker, dir)
+}
+
+/// Returns the squares strictly between `a` and `b` if they share a rank,
+/// file, or diagonal (and [`Bitboard::EMPTY`] otherwise)
+/// 
+/// Walks the rays on every call; [`get_between`] looks the result up instead
+pub fn between(a: Square, b: Square) -> Bitboard {
+    match Direction::all().find(|&dir| get_ray(a, dir).get(b)) {
+        Some(dir) => get_ray(a, dir) & !get_ray(b, dir) & !b.to_bitboard(),
+        None => Bitboard::EMPTY,
+    }
+}
+
+/// Returns every square on the line through `a` and `b` from one edge of the
+/// board to the other (including both) if they share a rank, file, or
+/// diagonal (and [`Bitboard::EMPTY`] otherwise)
+/// 
+/// Walks the rays on every call; [`get_line`] looks the result up instead
+pub fn line_through(a: Square, b: Square) -> Bitboard {
+    match Direction::all().find(|&dir| get_ray(a, dir).get(b)) {
+        Some(dir) => get_ray(a, dir) | get_ray(a, dir.opposite()) | a.to_bitboard(),
+        None => Bitboard::EMPTY,
+    }
+}
+
+/// Looks up [`between`]
+pub fn get_between(a: Square, b: Square) -> Bitboard {
     unsafe {
-        for (i, bb) in FILES_TABLE.iter_mut().enumerate() {
-            let f = File::convert(i as isize);
-            *bb = Bitboard::EMPTY;
-            for r_value in 0..8 {
-                let r = Rank::convert(r_value as isize);
-                let sq = Square::from(f, r);
-                match sq {
-                    Square::Null => (),
-                    Square::Sq(_) => bb.set(sq),
-                }
-            }
+        match (a, b) {
+            (Square::Sq(s1), Square::Sq(s2)) => BETWEEN_TABLE[s1 as usize][s2 as usize],
+            _ => panic!("Attempted to get squares between Square::Null"),
         }
     }
 }
 
-fn build_ranks() -> () {
+/// Looks up [`line_through`]
+pub fn get_line(a: Square, b: Square) -> Bitboard {
     unsafe {
-        for (i, bb) in RANKS_TABLE.iter_mut().enumerate() {
-            let r = Rank::convert(i as isize);
-            *bb = Bitboard::EMPTY;
-            for f_value in 0..8 {
-                let f = File::convert(f_value as isize);
-                let sq = Square::from(f, r);
-                match sq {
-                    Square::Null => (),
-                    Square::Sq(_) => bb.set(sq),
-                }
-            }
+        match (a, b) {
+            (Square::Sq(s1), Square::Sq(s2)) => LINE_TABLE[s1 as usize][s2 as usize],
+            _ => panic!("Attempted to get line through Square::Null"),
         }
     }
 }
 
+/// Returns the squares attacked by a bishop on `sq` given the occupancy
+pub fn get_bishop_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    get_ray_attacks(sq, Direction::Northeast, occ)
+        | get_ray_attacks(sq, Direction::Southeast, occ)
+        | get_ray_attacks(sq, Direction::Southwest, occ)
+        | get_ray_attacks(sq, Direction::Northwest, occ)
+}
+
+/// Returns the squares attacked by a rook on `sq` given the occupancy
+pub fn get_rook_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    get_ray_attacks(sq, Direction::North, occ)
+        | get_ray_attacks(sq, Direction::East, occ)
+        | get_ray_attacks(sq, Direction::South, occ)
+        | get_ray_attacks(sq, Direction::West, occ)
+}
+
+/// Returns the squares attacked by a queen on `sq` given the occupancy
+pub fn get_queen_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    get_bishop_attacks(sq, occ) | get_rook_attacks(sq, occ)
+}
+
+/// Returns the squares attacked by a rook on `sq` given the occupancy, with
+/// a single lookup in the magic bitboard table
+/// 
+/// Always agrees with [`get_rook_attacks`], but is much faster
+pub fn get_rook_attacks_magic(sq: Square, occ: Bitboard) -> Bitboard {
+    unsafe {
+        match (sq, occ) {
+            (Square::Null, _) => panic!("Attempted to get rook attacks from Square::Null"),
+            (_, Bitboard::Null) => panic!("Attempted to get rook attacks with Bitboard::Null"),
+            (Square::Sq(s), Bitboard::Bb(b)) => 
+                ROOK_MAGIC_TABLE[ROOK_MAGICS[s as usize].index(b)],
+        }
+    }
+}
+
+/// Returns the squares attacked by a bishop on `sq` given the occupancy,
+/// with a single lookup in the magic bitboard table
+/// 
+/// Always agrees with [`get_bishop_attacks`], but is much faster
+pub fn get_bishop_attacks_magic(sq: Square, occ: Bitboard) -> Bitboard {
+    unsafe {
+        match (sq, occ) {
+            (Square::Null, _) => panic!("Attempted to get bishop attacks from Square::Null"),
+            (_, Bitboard::Null) => panic!("Attempted to get bishop attacks with Bitboard::Null"),
+            (Square::Sq(s), Bitboard::Bb(b)) => 
+                BISHOP_MAGIC_TABLE[BISHOP_MAGICS[s as usize].index(b)],
+        }
+    }
+}
+
+/// Returns the squares attacked by a queen on `sq` given the occupancy,
+/// using the magic bitboard tables
+pub fn get_queen_attacks_magic(sq: Square, occ: Bitboard) -> Bitboard {
+    get_bishop_attacks_magic(sq, occ) | get_rook_attacks_magic(sq, occ)
+}
+
 fn build_pawn_moves() -> () {
     unsafe {
-        for (i, bb) in PAWN_MOVES_TABLE.iter_mut().enumerate() {
-            let sq = Square::new(i as u8);
+        for (bb, sq) in (*addr_of_mut!(PAWN_MOVES_TABLE)).iter_mut().zip(Square::all()) {
             *bb = Bitboard::EMPTY;
             match sq.rank() {
                 Rank::First | Rank::Eighth => continue,
@@ -168,11 +307,12 @@ fn build_pawn_moves() -> () {
 
 fn build_pawn_attacks() -> () {
     unsafe {
-        for (i, bb) in PAWN_ATTACKS_TABLE.iter_mut().enumerate() {
-            let sq = Square::new(i as u8);
+        for (bb, sq) in (*addr_of_mut!(PAWN_ATTACKS_TABLE)).iter_mut().zip(Square::all()) {
             *bb = Bitboard::EMPTY;
+            // First rank entries are kept since they are used to look up 
+            // which squares attack the first rank
             match (sq.rank(), sq.file()) {
-                (Rank::First, _) | (Rank::Eighth, _) => continue,
+                (Rank::Eighth, _) => continue,
                 (_, File::A) => bb.set(sq.rank_up().file_up()),
                 (_, File::H) => bb.set(sq.rank_up().file_down()),
                 (_, _) => {
@@ -184,99 +324,157 @@ fn build_pawn_attacks() -> () {
     }
 }
 
-fn build_knight_moves() -> () {
-    unsafe {
-        for (i, bb) in KNIGHT_TABLE.iter_mut().enumerate() {
-            let sq = Square::new(i as u8);
-            *bb = Bitboard::EMPTY;
-            for (dx, dy) in KNIGHT_OFFSETS {
-                let sq_ = sq.offset(dx, dy);
-                match sq_ {
-                    Square::Null => (),
-                    Square::Sq(_) => bb.set(sq_),
-                }
+/// Builds a table of the squares one of `offsets` away from each square
+/// 
+/// A `const fn` so that the knight and king tables need no initialization
+const fn build_offset_table(offsets: &[(i8, i8); 8]) -> [Bitboard; 64] {
+    let mut table = [Bitboard::EMPTY; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (file, rank) = ((sq % 8) as i8, (sq / 8) as i8);
+        let mut bits = 0u64;
+        let mut i = 0;
+        while i < offsets.len() {
+            let (f, r) = (file + offsets[i].0, rank + offsets[i].1);
+            if 0 <= f && f < 8 && 0 <= r && r < 8 {
+                bits |= 1u64 << (r * 8 + f);
             }
+            i += 1;
         }
+        table[sq] = Bitboard::Bb(bits);
+        sq += 1;
     }
+    table
 }
 
-fn build_king_moves() -> () {
+fn build_rays() -> () {
     unsafe {
-        for (i, bb) in KING_TABLE.iter_mut().enumerate() {
-            let sq = Square::new(i as u8);
-            *bb = Bitboard::EMPTY;
-            for (dx, dy) in KING_OFFSETS {
-                let sq_ = sq.offset(dx, dy);
-                match sq_ {
-                    Square::Null => (),
-                    Square::Sq(_) => bb.set(sq_),
+        let rays_table = &mut *addr_of_mut!(RAYS_TABLE);
+        for dir in Direction::all() {
+            for (bb, origin) in rays_table[dir as usize].iter_mut().zip(Square::all()) {
+                let mut sq = origin.to_bitboard().shift(dir);
+                *bb = Bitboard::EMPTY;
+                while !sq.is_empty() {
+                    *bb = *bb | sq;
+                    sq = sq.shift(dir);
                 }
             }
         }
     }
 }
 
-fn build_rays() -> () {
+fn build_lines() -> () {
     unsafe {
-        for (i, ray_array) in RAYS_TABLE.iter_mut().enumerate() {
-            let (dx, dy) = RAY_VECTORS[i];
-            for (j, bb) in ray_array.iter_mut().enumerate() {
-                let mut sq = Square::new(j as u8);
-                *bb = Bitboard::EMPTY;
-                loop {
-                    match dx {
-                        0i8 => (),
-                        1i8 => sq = sq.file_up(), 
-                        -1i8 => sq = sq.file_down(),
-                        _ => panic!("Invalid dx in ray vector")
-                    }
-                    if sq.is_null() { break; }
-                    match dy {
-                        0i8 => (),
-                        1i8 => sq = sq.rank_up(), 
-                        -1i8 => sq = sq.rank_down(),
-                        _ => panic!("Invalid dy in ray vector")
-                    }
-                    if sq.is_null() { break; }
-                    bb.set(sq);
-                }
+        let between_table = &mut *addr_of_mut!(BETWEEN_TABLE);
+        let line_table = &mut *addr_of_mut!(LINE_TABLE);
+        for a in Square::all() {
+            for b in Square::all() {
+                let (i, j) = (a.val() as usize, b.val() as usize);
+                between_table[i][j] = between(a, b);
+                line_table[i][j] = line_through(a, b);
             }
         }
     }
 }
 
+/// Finds a magic number for every square and fills in the attack `table`
+/// 
+/// `attacks` is the (slow) ray method, which gives the attacks the table
+/// must reproduce for every occupancy; a candidate magic is only accepted
+/// once every occupancy of its square has been checked against it.
+fn build_magics(magics: &mut [Magic; 64], table: &mut [Bitboard],
+                attacks: fn(Square, Bitboard) -> Bitboard) -> () {
+    let mut rng = MAGIC_SEED;
+    let mut offset = 0usize;
+    for (magic, sq) in magics.iter_mut().zip(Square::all()) {
+        // Pieces on the edge of the board never block anything
+        let edges = ((get_rank_bb(Rank::First) | get_rank_bb(Rank::Eighth))
+                     & !get_rank_bb(sq.rank()))
+            | ((get_file_bb(File::A) | get_file_bb(File::H)) & !get_file_bb(sq.file()));
+        let mask = attacks(sq, Bitboard::EMPTY).without(edges);
+        let bits = mask.pop_count() as u32;
+        let occupancies: Vec<(u64, Bitboard)> = mask.subsets()
+            .map(|sub| match sub {
+                Bitboard::Bb(b) => (b, attacks(sq, sub)),
+                Bitboard::Null => unreachable!(),
+            })
+            .collect();
+        let slots = &mut table[offset..offset + (1usize << bits)];
+        let mask = match mask {
+            Bitboard::Bb(b) => b,
+            Bitboard::Null => unreachable!(),
+        };
+        // Which attempt last wrote each slot, so slots needn't be cleared
+        let mut written = vec![0u32; slots.len()];
+        let mut attempt = 0u32;
+        loop {
+            // Candidates with few set bits make good magics
+            let candidate = next_random(&mut rng) & next_random(&mut rng) & next_random(&mut rng);
+            if (mask.wrapping_mul(candidate) >> 56).count_ones() < 6 {
+                continue;
+            }
+            attempt += 1;
+            *magic = Magic { mask, magic: candidate, shift: 64 - bits, offset: 0 };
+            let ok = occupancies.iter().all(|&(occ, att)| {
+                let i = magic.index(occ);
+                if written[i] != attempt {
+                    written[i] = attempt;
+                    slots[i] = att;
+                    true
+                } else {
+                    // Two occupancies may only share a slot if they have the
+                    // same attacks
+                    slots[i] == att
+                }
+            });
+            if ok {
+                break;
+            }
+        }
+        magic.offset = offset;
+        offset += slots.len();
+    }
+    debug_assert_eq!(offset, table.len());
+}
 
+/// Helper function, returns the next number from a xorshift generator
+pub(crate) fn next_random(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::board::bits::*;
 
-    // #[test]
+    #[test]
     fn test_files() {
-        build_files();
+        init();
         get_file_bb(File::A).print();
         get_file_bb(File::E).print();
     }
 
-    // #[test]
+    #[test]
     fn test_ranks() {
-        build_ranks();
+        init();
         get_rank_bb(Rank::First).print();
         get_rank_bb(Rank::Fourth).print();
     }
 
-    // #[test]
+    #[test]
     fn test_pawn_moves() {
-        build_pawn_moves();
+        init();
         get_pawn_moves(Square::from(File::E, Rank::Fourth)).print();
         get_pawn_moves(Square::from(File::B, Rank::Second)).print();
         get_pawn_moves(Square::from(File::A, Rank::First)).print();
     }
 
-    // #[test]
+    #[test]
     fn test_pawn_attacks() {
-        build_pawn_attacks();
+        init();
         get_pawn_attacks(Square::from(File::E, Rank::Fourth)).print();
         get_pawn_attacks(Square::from(File::B, Rank::Second)).print();
         get_pawn_attacks(Square::from(File::A, Rank::First)).print();
@@ -284,23 +482,43 @@ mod tests {
         get_pawn_attacks(Square::from(File::H, Rank::Seventh)).print();
     }
 
-    // #[test]
+    #[test]
     fn test_knights() {
-        build_knight_moves();
+        init();
         get_knight_moves(Square::from(File::E, Rank::Fourth)).print();
         get_knight_moves(Square::from(File::H, Rank::Seventh)).print();
     }
 
-    // #[test]
+    #[test]
     fn test_kings() {
-        build_king_moves();
+        init();
         get_king_moves(Square::from(File::E, Rank::Fourth)).print();
         get_king_moves(Square::from(File::H, Rank::Seventh)).print();
     }
 
-    // #[test]
+    #[test]
+    fn test_const_offset_tables() {
+        // No init: the tables are built at compile time. Check them against
+        // stepping by the offsets one square at a time
+        let stepped = |sq: Square, offsets: &[(i8, i8)]| -> Bitboard {
+            offsets.iter()
+                .map(|&(dx, dy)| sq.offset(dx, dy))
+                .filter(|s| !s.is_null())
+                .collect()
+        };
+        for (f, r) in [(File::A, Rank::First), (File::E, Rank::Fourth),
+                       (File::H, Rank::Seventh), (File::B, Rank::Eighth)] {
+            let sq = Square::from(f, r);
+            assert_eq!(get_knight_moves(sq), stepped(sq, &KNIGHT_OFFSETS), "{}", sq);
+            assert_eq!(get_king_moves(sq), stepped(sq, &KING_OFFSETS), "{}", sq);
+        }
+        assert_eq!(get_knight_moves(Square::from(File::A, Rank::First)).pop_count(), 2);
+        assert_eq!(get_king_moves(Square::from(File::E, Rank::Fourth)).pop_count(), 8);
+    }
+
+    #[test]
     fn test_rays() {
-        build_rays();
+        init();
         get_ray(Square::from(File::E, Rank::Fourth), Direction::North).print();
         get_ray(Square::from(File::B, Rank::Second), Direction::Northeast).print();
         get_ray(Square::from(File::A, Rank::Eighth), Direction::South).print();
@@ -308,9 +526,9 @@ mod tests {
         get_ray(Square::from(File::A, Rank::Eighth), Direction::Northwest).print();
     }
 
-    // #[test]
+    #[test]
     fn test_all() {
-        build();
+        init();
         get_file_bb(File::A).print();
         get_rank_bb(Rank::First).print();
         get_pawn_moves(Square::from(File::B, Rank::Second)).print();
@@ -319,5 +537,66 @@ mod tests {
         get_king_moves(Square::from(File::E, Rank::Fourth)).print();
         get_ray(Square::from(File::B, Rank::Second), Direction::East).print();
     }
-}
+    #[test]
+    fn test_slider_attacks() {
+        init();
+        let e4 = Square::from(File::E, Rank::Fourth);
+        let mut occ = Bitboard::EMPTY;
+        occ.set(Square::from(File::E, Rank::Sixth));
+        occ.set(Square::from(File::C, Rank::Fourth));
+        occ.set(Square::from(File::G, Rank::Sixth));
+        let rook = get_rook_attacks(e4, occ);
+        assert_eq!(rook.pop_count(), 2 + 2 + 3 + 3);
+        assert!(rook.get(Square::from(File::E, Rank::Sixth)));
+        assert!(!rook.get(Square::from(File::E, Rank::Seventh)));
+        assert!(rook.get(Square::from(File::C, Rank::Fourth)));
+        assert!(!rook.get(Square::from(File::B, Rank::Fourth)));
+        let bishop = get_bishop_attacks(e4, occ);
+        assert_eq!(bishop.pop_count(), 2 + 3 + 3 + 4);
+        assert!(!bishop.get(Square::from(File::H, Rank::Seventh)));
+        assert_eq!(get_queen_attacks(e4, occ), rook | bishop);
+    }
+
+    #[test]
+    fn test_between_line() {
+        init();
+        let a1 = Square::from(File::A, Rank::First);
+        let h8 = Square::from(File::H, Rank::Eighth);
+        let e4 = Square::from(File::E, Rank::Fourth);
+        let e7 = Square::from(File::E, Rank::Seventh);
+        let f6 = Square::from(File::F, Rank::Sixth);
+        assert_eq!(get_between(a1, h8).pop_count(), 6);
+        assert_eq!(get_line(a1, e4), Bitboard::EMPTY);
+        assert_eq!(get_between(e4, e7),
+                   Square::from(File::E, Rank::Fifth).to_bitboard()
+                   | Square::from(File::E, Rank::Sixth).to_bitboard());
+        assert_eq!(get_line(e7, e4), get_file_bb(File::E));
+        assert_eq!(get_between(e4, f6), Bitboard::EMPTY);
+        assert_eq!(get_between(e4, e4), Bitboard::EMPTY);
+        let mut rng = 0x0fed_cba9_8765_4321u64;
+        for _ in 0..256 {
+            let a = Square::new((next_random(&mut rng) % 64) as u8);
+            let b = Square::new((next_random(&mut rng) % 64) as u8);
+            assert_eq!(get_between(a, b), between(a, b));
+            assert_eq!(get_between(a, b), get_between(b, a));
+            assert_eq!(get_line(a, b), line_through(a, b));
+        }
+    }
 
+    #[test]
+    fn test_magic_attacks() {
+        init();
+        let mut rng = 0x1234_5678_9abc_def0u64;
+        for sq in Square::all() {
+            for _ in 0..64 {
+                // Sparse and dense occupancies
+                let occ = Bitboard::new(next_random(&mut rng) & next_random(&mut rng));
+                assert_eq!(get_rook_attacks_magic(sq, occ), get_rook_attacks(sq, occ));
+                assert_eq!(get_bishop_attacks_magic(sq, occ), get_bishop_attacks(sq, occ));
+                let occ = Bitboard::new(next_random(&mut rng) | next_random(&mut rng));
+                assert_eq!(get_rook_attacks_magic(sq, occ), get_rook_attacks(sq, occ));
+                assert_eq!(get_queen_attacks_magic(sq, occ), get_queen_attacks(sq, occ));
+            }
+        }
+    }
+}
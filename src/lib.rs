@@ -0,0 +1,13 @@
+//! A chess library + engine written in Rust.
+
+// Explicit `-> ()` return types are part of the house style
+#![allow(clippy::unused_unit)]
+
+pub mod board;
+pub mod book;
+pub mod eval;
+pub mod game;
+pub mod helper;
+pub mod movegen;
+pub mod search;
+pub mod uci;
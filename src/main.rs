@@ -1,9 +1,7 @@
 //! A chess library + engine written in Rust.
-
-pub mod board;
-pub mod helper;
-pub mod movegen;
+//!
+//! Speaks UCI on stdin and stdout, so it can be loaded into a chess GUI.
 
 fn main() {
-    println!("Hello, world!");
-}
\ No newline at end of file
+    rustmonkey::uci::run();
+}
@@ -0,0 +1,713 @@
+//! Move representation and move generation
+
+use crate::board::{Board, Color, Piece, PieceType, Side, Whose};
+use crate::board::variant::Variant;
+use crate::board::bits::{Bitboard, File, Rank, Square};
+use crate::helper::tables;
+use std::cmp::Ordering;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+mod perft;
+mod san;
+
+pub use perft::{perft, perft_hashed};
+pub use san::AnnotatedMove;
+
+/// A move from the perspective of the side to move
+///
+/// Squares are relative to the board of the player making the move (i.e.
+/// our pawns always move up the board). Castling moves are described by the
+/// king's origin and destination squares, or in Chess960 (see
+/// [`Board::is_chess960`]) by the king's and the castling rook's. Crazyhouse drops place a piece
+/// from our pocket onto `to` and have a `from` of [`Square::Null`].
+///
+/// Moves are ordered by `from`, then `to`, then `promotion` (drops, with no
+/// `from`, come first), which is enough to tell apart any two moves in the
+/// same position. The remaining fields only break ties so that the ordering
+/// agrees with `==`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Move {
+    pub to: Square,
+    pub from: Square,
+    /// Type of the piece that moves (the king when castling, and the piece
+    /// dropped for drops)
+    pub piece: PieceType,
+    pub capture: bool,
+    /// Type of the piece captured (a pawn for en passant)
+    pub captured: Option<PieceType>,
+    /// Double pawn push
+    pub dpp: bool,
+    pub promotion: Option<PieceType>,
+    pub castling: Option<Side>,
+    /// Piece type dropped from our pocket (see [`Variant::Crazyhouse`])
+    pub drop: Option<PieceType>,
+}
+
+impl Move {
+    /// A sentinel for "no move" (e.g. an empty PV slot, or the pass made by
+    /// null-move pruning), with both `from` and `to` set to [`Square::Null`]
+    ///
+    /// `NULL` never appears in a generated move list, and must not be given
+    /// to [`Board::make`]; pass with [`Board::make_null`] instead.
+    pub const NULL: Move = Move {
+        to: Square::Null,
+        from: Square::Null,
+        piece: PieceType::K,
+        capture: false,
+        captured: None,
+        dpp: false,
+        promotion: None,
+        castling: None,
+        drop: None,
+    };
+
+    /// Returns whether or not this is [`Move::NULL`]
+    pub fn is_null(&self) -> bool {
+        self.from.is_null() && self.to.is_null()
+    }
+
+    /// Helper function, returns the key moves are ordered by
+    fn sort_key(&self) -> (Option<u8>, Option<u8>, Option<u8>, Option<u8>) {
+        (self.from.try_val(), self.to.try_val(),
+         self.promotion.map(|pt| pt as u8), self.drop.map(|pt| pt as u8))
+    }
+
+    /// Helper function, returns the fields not in [`sort_key`](Move::sort_key)
+    fn tie_break(&self) -> (u8, bool, Option<u8>, bool, Option<u8>) {
+        (self.piece as u8, self.capture, self.captured.map(|pt| pt as u8),
+         self.dpp, self.castling.map(|cs| cs as u8))
+    }
+
+    /// Returns the move in UCI's long algebraic notation (e.g. `e7e8q`)
+    /// 
+    /// `c` is the color of the player making the move, which is needed to
+    /// map the relative squares back onto the real board. [`Move::NULL`] is
+    /// written as `0000`
+    pub fn to_uci(&self, c: Color) -> String {
+        if self.is_null() {
+            return "0000".to_string();
+        }
+        if let Some(pt) = self.drop {
+            let to = match c {
+                Color::White => self.to,
+                Color::Black => self.to.flipped(),
+            };
+            return format!("{}@{}", Piece::Pc(Whose::Ours, pt).to_char(), to);
+        }
+        let (from, to) = match c {
+            Color::White => (self.from, self.to),
+            Color::Black => (self.from.flipped(), self.to.flipped()),
+        };
+        let mut uci = format!("{}{}", from, to);
+        if let Some(pt) = self.promotion {
+            uci.push(match pt {
+                PieceType::N => 'n',
+                PieceType::B => 'b',
+                PieceType::R => 'r',
+                PieceType::Q => 'q',
+                _ => panic!("Attempted to promote to {:?}", pt),
+            });
+        }
+        uci
+    }
+
+    /// Parses a move in UCI's long algebraic notation, returning it if it is
+    /// legal on `board`
+    pub fn from_uci(uci: &str, board: &Board) -> Option<Move> {
+        let color = board.color();
+        generate_legal(board).into_iter().find(|m| m.to_uci(color) == uci)
+    }
+}
+
+/// Piece types a pawn can promote to
+const PROMOTIONS: [PieceType; 4] = [PieceType::Q, PieceType::R, PieceType::B, PieceType::N];
+
+/// Generates every pseudo-legal move for the side to move
+/// 
+/// Pseudo-legal moves follow the movement rules of each piece but may leave
+/// our own king in check. Castling is the exception: it is only generated
+/// when the king does not start on, pass through, or land on an attacked
+/// square.
+pub fn generate_pseudo_legal(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let pawns = board.get_pieces(Piece::Pc(Whose::Ours, PieceType::P));
+    generate_pawn_pushes(board, pawns, &mut moves);
+    generate_pawn_captures(board, pawns, &mut moves);
+    let targets = !board.get_whose(Whose::Ours);
+    for pt in [PieceType::N, PieceType::B, PieceType::R, PieceType::Q, PieceType::K] {
+        generate_piece_moves(board, pt, targets, &mut moves);
+    }
+    generate_castling(board, &mut moves);
+    if board.variant() == Variant::Crazyhouse {
+        generate_drops(board, &mut moves);
+    }
+    moves
+}
+
+/// Generates every legal capture for the side to move (including en passant
+/// and capturing promotions)
+/// 
+/// Only moves onto their pieces are generated in the first place, which is
+/// much cheaper than filtering every legal move (e.g. in quiescence search)
+pub fn generate_captures(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    let pawns = board.get_pieces(Piece::Pc(Whose::Ours, PieceType::P));
+    generate_pawn_captures(board, pawns, &mut moves);
+    let targets = board.get_whose(Whose::Theirs);
+    for pt in [PieceType::N, PieceType::B, PieceType::R, PieceType::Q, PieceType::K] {
+        generate_piece_moves(board, pt, targets, &mut moves);
+    }
+    moves.retain(|m| is_legal(board, *m));
+    moves
+}
+
+/// Generates every legal move for the side to move
+/// 
+/// Each move appears exactly once: every generator covers a distinct kind of
+/// move (e.g. en passant is only generated onto the empty en passant square,
+/// which ordinary pawn captures never target). In check, only evasions are
+/// generated (see [`generate_evasions`]).
+pub fn generate_legal(board: &Board) -> Vec<Move> {
+    if board.variant() != Variant::Atomic && board.is_in_check() {
+        return generate_evasions(board);
+    }
+    generate_pseudo_legal(board)
+        .into_iter()
+        .filter(|m| is_legal(board, *m))
+        .collect()
+}
+
+/// Generates every legal move for a side to move that is in check
+/// 
+/// Only moves that could possibly get out of check are generated: king
+/// moves, and in single check also captures of the checking piece and moves
+/// onto the squares between it and the king. In double check only the king
+/// can move. If not in check at all, every legal move is generated.
+/// 
+/// Not for atomic chess, where exploding their king is always an answer.
+pub fn generate_evasions(board: &Board) -> Vec<Move> {
+    let checkers = board.checkers();
+    if checkers.is_empty() {
+        return generate_pseudo_legal(board)
+            .into_iter()
+            .filter(|m| is_legal(board, *m))
+            .collect();
+    }
+    let mut moves = Vec::new();
+    generate_piece_moves(board, PieceType::K, !board.get_whose(Whose::Ours), &mut moves);
+    if checkers.is_singular() {
+        let king = board.kings[Whose::Ours as usize];
+        let checker = checkers.lsb();
+        let blocks = tables::get_between(king, checker);
+        let targets = checkers | blocks;
+        let mut pawn_moves = Vec::new();
+        let pawns = board.get_pieces(Piece::Pc(Whose::Ours, PieceType::P));
+        generate_pawn_pushes(board, pawns, &mut pawn_moves);
+        generate_pawn_captures(board, pawns, &mut pawn_moves);
+        // En passant lands behind the checker rather than on it
+        let en_passant = board.en_passant();
+        let ep_captures_checker = !en_passant.is_null() && en_passant.rank_down() == checker;
+        moves.extend(pawn_moves.into_iter().filter(|m| {
+            targets.get(m.to) || (ep_captures_checker && m.to == en_passant)
+        }));
+        for pt in [PieceType::N, PieceType::B, PieceType::R, PieceType::Q] {
+            generate_piece_moves(board, pt, targets, &mut moves);
+        }
+        if board.variant() == Variant::Crazyhouse {
+            let mut drops = Vec::new();
+            generate_drops(board, &mut drops);
+            moves.extend(drops.into_iter().filter(|m| blocks.get(m.to)));
+        }
+    }
+    moves.retain(|m| is_legal(board, *m));
+    moves
+}
+
+/// Generates the legal moves of the piece on `sq` (e.g. to show where a
+/// clicked piece can go)
+/// 
+/// `sq` is relative to the side to move, like every square on the board.
+/// Returns no moves if `sq` doesn't hold one of our pieces. Castling counts
+/// as a move of the king.
+pub fn moves_from(board: &Board, sq: Square) -> Vec<Move> {
+    let mut moves = Vec::new();
+    match board.get(sq) {
+        Piece::Pc(Whose::Ours, PieceType::P) => {
+            generate_pawn_pushes(board, sq.to_bitboard(), &mut moves);
+            generate_pawn_captures(board, sq.to_bitboard(), &mut moves);
+        },
+        Piece::Pc(Whose::Ours, pt) => {
+            generate_moves_from(board, sq, pt, !board.get_whose(Whose::Ours), &mut moves);
+            if pt == PieceType::K {
+                generate_castling(board, &mut moves);
+            }
+        },
+        _ => (),
+    }
+    moves.retain(|m| is_legal(board, *m));
+    moves
+}
+
+/// Returns whether or not a pseudo-legal move leaves our king safe
+/// 
+/// The move is played out on a copy of the board before looking for checks,
+/// so an en passant capture that takes both pawns off our king's rank (and
+/// uncovers a rook or queen behind them) is caught like any other pin.
+/// 
+/// In atomic chess a move may not blow up our own king, but blowing up
+/// their king wins outright, even if it leaves ours in check
+pub fn is_legal(board: &Board, m: Move) -> bool {
+    let mut bd = *board;
+    bd.apply_move(m);
+    let had_king = |b: &Board, w: Whose| !b.kings[w as usize].is_null();
+    if had_king(board, Whose::Ours) && !had_king(&bd, Whose::Ours) {
+        return false;
+    }
+    if had_king(board, Whose::Theirs) && !had_king(&bd, Whose::Theirs) {
+        return true;
+    }
+    !bd.is_in_check()
+}
+
+/// Helper function, generates single and double pawn pushes (including
+/// promotions) of our `pawns`
+fn generate_pawn_pushes(board: &Board, pawns: Bitboard, moves: &mut Vec<Move>) -> () {
+    for from in pawns {
+        let to = from.rank_up();
+        if board.get(to) == Piece::Empty {
+            push_pawn_move(moves, from, to, None);
+            if from.rank() == Rank::Second {
+                let to2 = to.rank_up();
+                if board.get(to2) == Piece::Empty {
+                    moves.push(Move {
+                        to: to2,
+                        from,
+                        piece: PieceType::P,
+                        capture: false,
+                        captured: None,
+                        dpp: true,
+                        promotion: None,
+                        castling: None,
+                        drop: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Helper function, generates pawn captures (including promotions) and en
+/// passant captures by our `pawns`
+fn generate_pawn_captures(board: &Board, pawns: Bitboard, moves: &mut Vec<Move>) -> () {
+    let theirs = board.get_whose(Whose::Theirs);
+    let en_passant = board.en_passant();
+    for from in pawns {
+        let attacks = tables::get_pawn_attacks(from);
+        for to in attacks & theirs {
+            push_pawn_move(moves, from, to, board.get(to).piece_type());
+        }
+        if !en_passant.is_null() && attacks.get(en_passant) {
+            push_pawn_move(moves, from, en_passant, Some(PieceType::P));
+        }
+    }
+}
+
+/// Helper function, adds a single-step pawn move (expanding promotions)
+fn push_pawn_move(moves: &mut Vec<Move>, from: Square, to: Square,
+                  captured: Option<PieceType>) -> () {
+    let m = Move {
+        to,
+        from,
+        piece: PieceType::P,
+        capture: captured.is_some(),
+        captured,
+        dpp: false,
+        promotion: None,
+        castling: None,
+        drop: None,
+    };
+    if to.rank() == Rank::Eighth {
+        for pt in PROMOTIONS {
+            moves.push(Move { promotion: Some(pt), ..m });
+        }
+    } else {
+        moves.push(m);
+    }
+}
+
+/// Helper function, generates the moves of every piece of type `pt` (other
+/// than pawns) onto the squares in `targets`
+fn generate_piece_moves(board: &Board, pt: PieceType, targets: Bitboard,
+                        moves: &mut Vec<Move>) -> () {
+    for from in board.get_pieces(Piece::Pc(Whose::Ours, pt)) {
+        generate_moves_from(board, from, pt, targets, moves);
+    }
+}
+
+/// Helper function, generates the moves of our piece of type `pt` (other
+/// than a pawn) on `from` onto the squares in `targets`
+fn generate_moves_from(board: &Board, from: Square, pt: PieceType, targets: Bitboard,
+                       moves: &mut Vec<Move>) -> () {
+    for to in board.attacks_from(from) & targets {
+        let captured = board.get(to).piece_type();
+        moves.push(Move {
+            to,
+            from,
+            piece: pt,
+            capture: captured.is_some(),
+            captured,
+            dpp: false,
+            promotion: None,
+            castling: None,
+            drop: None,
+        });
+    }
+}
+
+/// Helper function, generates drops of every piece in our pocket onto the
+/// empty squares (pawns can't be dropped on the first or last rank)
+fn generate_drops(board: &Board, moves: &mut Vec<Move>) -> () {
+    let empty = !(board.get_whose(Whose::Ours) | board.get_whose(Whose::Theirs));
+    for pt in PieceType::all().filter(|&pt| board.pocket(Whose::Ours, pt) > 0) {
+        for to in empty {
+            if pt == PieceType::P && matches!(to.rank(), Rank::First | Rank::Eighth) {
+                continue;
+            }
+            moves.push(Move {
+                to,
+                from: Square::Null,
+                piece: pt,
+                capture: false,
+                captured: None,
+                dpp: false,
+                promotion: None,
+                castling: None,
+                drop: Some(pt),
+            });
+        }
+    }
+}
+
+/// Helper function, generates castling moves
+/// 
+/// Every square the king and rook pass over or land on must be empty (apart
+/// from the king and rook themselves), and none of the king's squares may be
+/// attacked. In Chess960 the move goes from the king to the rook.
+fn generate_castling(board: &Board, moves: &mut Vec<Move>) -> () {
+    for cs in [Side::K, Side::Q] {
+        if !board.castling_get(Whose::Ours, cs) {
+            continue;
+        }
+        let (king, king_to, rook, rook_to) = board.castling_squares(cs);
+        if king.is_null() || king.rank() != Rank::First
+            || board.get(rook) != Piece::Pc(Whose::Ours, PieceType::R) {
+            continue;
+        }
+        let files = [king, king_to, rook, rook_to].map(|sq| sq.file() as i8);
+        let lo = *files.iter().min().unwrap();
+        let hi = *files.iter().max().unwrap();
+        let blocked = (lo..=hi)
+            .map(|f| Square::from(File::convert(f as isize), Rank::First))
+            .any(|sq| sq != king && sq != rook && board.get(sq) != Piece::Empty);
+        // The king may not castle out of, through, or into check
+        let dx: i8 = if (king_to.file() as i8) > (king.file() as i8) { 1 } else { -1 };
+        let mut sq = king;
+        let mut attacked = board.is_attacked(sq, Whose::Theirs);
+        while sq != king_to {
+            sq = sq.offset(dx, 0);
+            attacked |= board.is_attacked(sq, Whose::Theirs);
+        }
+        if !blocked && !attacked {
+            moves.push(Move {
+                to: if board.is_chess960() { rook } else { king_to },
+                from: king,
+                piece: PieceType::K,
+                capture: false,
+                captured: None,
+                dpp: false,
+                promotion: None,
+                castling: Some(cs),
+                drop: None,
+            });
+        }
+    }
+}
+
+impl Ord for Move {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+            .then_with(|| self.tie_break().cmp(&other.tie_break()))
+    }
+}
+
+impl PartialOrd for Move {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::bits::File;
+
+    #[test]
+    fn test_generate_legal_start() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(generate_legal(&bd).len(), 20);
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(generate_legal(&bd).len(), 20);
+    }
+
+    #[test]
+    fn test_generate_legal_kiwipete() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let moves = generate_legal(&bd);
+        assert_eq!(moves.len(), 48);
+        assert_eq!(moves.iter().filter(|m| m.capture).count(), 8);
+        assert_eq!(moves.iter().filter(|m| m.castling.is_some()).count(), 2);
+        // The same position with colors reversed
+        bd.board_from_fen(
+            "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b KQkq - 0 1");
+        let moves = generate_legal(&bd);
+        assert_eq!(moves.len(), 48);
+        assert_eq!(moves.iter().filter(|m| m.castling.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn test_generate_legal_no_duplicates() {
+        use std::collections::HashSet;
+
+        tables::init();
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ];
+        // A tiny xorshift generator keeps the random games reproducible
+        let mut state = 0x2545F4914F6CDD1Du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for fen in fens {
+            for _ in 0..20 {
+                let mut bd = Board::new();
+                bd.board_from_fen(fen);
+                for _ in 0..40 {
+                    let moves = generate_legal(&bd);
+                    let unique: HashSet<_> = moves.iter().collect();
+                    assert_eq!(unique.len(), moves.len(), "{}", bd.to_fen());
+                    if moves.is_empty() {
+                        break;
+                    }
+                    let m = moves[(next() % moves.len() as u64) as usize];
+                    bd.make(m);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_uci() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppp1ppp/8/8/3pP3/8/PPP2PPP/RNBQKBNR b KQkq e3 0 3");
+        let m = Move::from_uci("d4e3", &bd).unwrap();
+        assert_eq!(m.to_uci(Color::Black), "d4e3");
+        assert!(m.capture);
+        assert_eq!(Move::from_uci("d4d3", &bd).map(|m| m.capture), Some(false));
+        assert_eq!(Move::from_uci("e7e4", &bd), None);
+        assert_eq!(Move::from_uci("nonsense", &bd), None);
+    }
+
+    #[test]
+    fn test_generate_evasions() {
+        tables::init();
+        let mut bd = Board::new();
+        let positions = [
+            // Single checks: block, capture, or run
+            "4k3/8/8/8/1b6/8/2N5/RN2K3 w - - 0 1",
+            "rnbqkbnr/ppp2ppp/8/1B1pp3/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 3",
+            // Check from a knight can't be blocked
+            "4k3/8/8/8/8/3n4/2P5/R3K2R w KQ - 0 1",
+            // The checking pawn can be taken en passant
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            // Double check
+            "4k3/8/3N4/8/8/8/8/4R1K1 b - - 0 1",
+            // Promoting by capturing the checker
+            "3rk3/4P3/8/8/8/8/8/3K4 w - - 0 1",
+        ];
+        for fen in positions {
+            bd.board_from_fen(fen);
+            assert!(bd.is_in_check(), "{}", fen);
+            let color = bd.color();
+            let mut evasions: Vec<String> = generate_evasions(&bd).iter()
+                .map(|m| m.to_uci(color))
+                .collect();
+            let mut legal: Vec<String> = generate_pseudo_legal(&bd).into_iter()
+                .filter(|m| is_legal(&bd, *m))
+                .map(|m| m.to_uci(color))
+                .collect();
+            evasions.sort();
+            legal.sort();
+            assert_eq!(evasions, legal, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_generate_captures() {
+        tables::init();
+        let mut bd = Board::new();
+        let fens = [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+        ];
+        for fen in fens {
+            bd.board_from_fen(fen);
+            let captures = generate_captures(&bd);
+            let legal = generate_legal(&bd).into_iter().filter(|m| m.capture).count();
+            assert_eq!(captures.len(), legal, "{}", fen);
+            assert!(captures.iter().all(|m| m.capture));
+        }
+    }
+
+    #[test]
+    fn test_generate_legal_special() {
+        tables::init();
+        let mut bd = Board::new();
+        // En passant and promotions (with and without capture)
+        bd.board_from_fen("1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1");
+        let moves = generate_legal(&bd);
+        assert_eq!(moves.iter().filter(|m| m.promotion.is_some()).count(), 8);
+        assert_eq!(moves.iter().filter(|m| m.to == Square::from(File::D, Rank::Sixth)).count(), 1);
+        // Castling through an attacked square (f1) is not allowed
+        bd.board_from_fen("4k3/8/8/8/8/8/5r2/R3K2R w KQ - 0 1");
+        let moves = generate_legal(&bd);
+        assert!(moves.iter().all(|m| m.castling != Some(Side::K)));
+        assert!(moves.iter().any(|m| m.castling == Some(Side::Q)));
+        // Nor is castling out of check
+        bd.board_from_fen("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1");
+        let moves = generate_legal(&bd);
+        assert!(moves.iter().all(|m| m.castling.is_none()));
+    }
+
+    #[test]
+    fn test_generate_piece_types() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("1n2k3/P7/8/3pP3/8/8/8/4K2R w K d6 0 1");
+        for m in generate_legal(&bd) {
+            assert_eq!(m.captured.is_some(), m.capture, "{}", m.to_uci(Color::White));
+            if m.capture {
+                assert_eq!(m.captured, bd.get(m.to).piece_type().or(Some(PieceType::P)));
+            }
+            if m.castling.is_some() {
+                assert_eq!(m.piece, PieceType::K);
+            } else {
+                assert_eq!(Some(m.piece), bd.get(m.from).piece_type());
+            }
+        }
+    }
+
+    #[test]
+    fn test_move_ordering() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        let moves = generate_legal(&bd);
+        let again = generate_legal(&bd);
+        assert_eq!(moves[0], again[0]);
+        assert_eq!(moves[0].cmp(&again[0]), Ordering::Equal);
+        let mut sorted = moves.clone();
+        sorted.sort();
+        let mut reversed = moves.clone();
+        reversed.reverse();
+        reversed.sort();
+        assert_eq!(sorted, reversed);
+        let key = |m: &Move| (m.from.val(), m.to.val());
+        assert!(sorted.windows(2).all(|w| key(&w[0]) <= key(&w[1])));
+        // Promotions to the same square are told apart by the piece
+        bd.board_from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1");
+        let mut promotions: Vec<Move> = generate_legal(&bd).into_iter()
+            .filter(|m| m.promotion.is_some())
+            .collect();
+        promotions.sort();
+        promotions.dedup();
+        assert_eq!(promotions.len(), 4);
+    }
+
+    #[test]
+    fn test_null() {
+        tables::init();
+        assert!(Move::NULL.is_null());
+        assert_eq!(Move::NULL.to_uci(Color::Black), "0000");
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert!(generate_legal(&bd).iter().all(|m| !m.is_null()));
+        // Drops have no origin square, but aren't null
+        bd.board_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        bd.set_variant(Variant::Crazyhouse);
+        bd.pocket_add(Whose::Ours, PieceType::N);
+        let drop = generate_legal(&bd).into_iter().find(|m| m.drop.is_some()).unwrap();
+        assert!(!drop.is_null());
+    }
+
+    #[test]
+    fn test_moves_from() {
+        tables::init();
+        let mut bd = Board::new();
+        // The knight on d2 is pinned by the bishop on b4
+        bd.board_from_fen("4k3/8/8/8/1b6/8/3N4/R3K2R w KQ - 0 1");
+        assert!(moves_from(&bd, Square::from(File::D, Rank::Second)).is_empty());
+        // Castling both ways comes with the king
+        let king = moves_from(&bd, Square::from(File::E, Rank::First));
+        assert_eq!(king.iter().filter(|m| m.castling.is_some()).count(), 2);
+        // Empty squares and their pieces have no moves
+        assert!(moves_from(&bd, Square::from(File::E, Rank::Fourth)).is_empty());
+        assert!(moves_from(&bd, Square::from(File::B, Rank::Fourth)).is_empty());
+        // Every piece's moves add up to the whole list, in check too
+        for fen in ["r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                    "rnbqkbnr/ppp2ppp/8/1B1pp3/4P3/8/PPPP1PPP/RNBQK1NR b KQkq - 1 3",
+                    "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1"] {
+            bd.board_from_fen(fen);
+            let mut each: Vec<Move> = Square::all().flat_map(|sq| moves_from(&bd, sq)).collect();
+            let mut all = generate_legal(&bd);
+            each.sort();
+            all.sort();
+            assert_eq!(each, all, "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_en_passant_discovered_check() {
+        tables::init();
+        let mut bd = Board::new();
+        // bxc6 would take both pawns off the fifth rank, leaving the king on
+        // a5 in check from the rook on h5
+        for fen in ["7k/8/8/KPp4r/8/8/8/8 w - c6 0 1",
+                    "8/8/8/8/R4Ppk/8/8/K7 b - f3 0 1"] {
+            bd.board_from_fen(fen);
+            let ep = bd.en_passant();
+            assert!(!ep.is_null(), "{}", fen);
+            assert!(generate_pseudo_legal(&bd).iter().any(|m| m.to == ep), "{}", fen);
+            assert!(generate_legal(&bd).iter().all(|m| m.to != ep), "{}", fen);
+            assert!(generate_captures(&bd).is_empty(), "{}", fen);
+        }
+        // Without the rook it's fine
+        bd.board_from_fen("7k/8/8/KPp5/8/8/8/8 w - c6 0 1");
+        assert!(generate_legal(&bd).iter().any(|m| m.to == bd.en_passant()));
+    }
+}
@@ -0,0 +1,117 @@
+//! Perft (performance test) for validating and timing move generation
+
+use super::*;
+
+/// Counts the leaf nodes of the legal move tree `depth` plies deep
+/// 
+/// The counts for well-known positions are published, which makes this the
+/// standard check that move generation is correct
+pub fn perft(board: &Board, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    let moves = generate_legal(board);
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.iter()
+        .map(|&m| {
+            let mut bd = *board;
+            bd.play(m);
+            perft(&bd, depth - 1)
+        })
+        .sum()
+}
+
+/// Like [`perft`], but also checks the incrementally updated hash against a
+/// from-scratch [`zobrist_hash`](Board::zobrist_hash) at every node
+/// 
+/// Much slower than [`perft`], since no node (not even a leaf) is skipped.
+/// Panics with the offending position if a move forgot to update the hash.
+pub fn perft_hashed(board: &Board, depth: u32) -> u64 {
+    assert_eq!(board.hash(), board.zobrist_hash(), "Hash mismatch at {}", board.to_fen());
+    if depth == 0 {
+        return 1;
+    }
+    generate_legal(board).iter()
+        .map(|&m| {
+            let mut bd = *board;
+            bd.play(m);
+            perft_hashed(&bd, depth - 1)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_perft_start() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(perft(&bd, 1), 20);
+        assert_eq!(perft(&bd, 2), 400);
+        assert_eq!(perft(&bd, 3), 8902);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(perft(&bd, 1), 48);
+        assert_eq!(perft(&bd, 2), 2039);
+        assert_eq!(perft(&bd, 3), 97862);
+    }
+
+    #[test]
+    fn test_perft_hashed() {
+        tables::init();
+        let mut bd = Board::new();
+        // Kiwipete has castling, en passant, and promotions within 4 plies
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(perft_hashed(&bd, 4), 4085603);
+    }
+
+    #[test]
+    fn test_perft_tricky() {
+        tables::init();
+        let mut bd = Board::new();
+        // Position 3 from the Chess Programming Wiki (en passant pins)
+        bd.board_from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+        assert_eq!(perft(&bd, 3), 2812);
+        assert_eq!(perft(&bd, 4), 43238);
+        // Position 4 (promotions and castling rights)
+        bd.board_from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1");
+        assert_eq!(perft(&bd, 3), 9467);
+    }
+
+    #[test]
+    fn test_perft_chess960() {
+        tables::init();
+        let mut bd = Board::new();
+        // Positions 1 to 3 of the published Chess960 perft results
+        bd.board_from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9");
+        assert_eq!(perft(&bd, 1), 21);
+        assert_eq!(perft(&bd, 2), 528);
+        assert_eq!(perft(&bd, 3), 12189);
+        assert_eq!(perft(&bd, 4), 326672);
+        bd.board_from_fen("2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9");
+        assert_eq!(perft(&bd, 3), 18002);
+        bd.board_from_fen("b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9");
+        assert_eq!(perft(&bd, 4), 273318);
+        // The standard start position is one of the 960, and counts the same
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1");
+        assert!(bd.is_chess960());
+        assert_eq!(perft(&bd, 3), 8902);
+        // So does Kiwipete, with its castling moves written king-takes-rook
+        bd.board_from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w HAha - 0 1");
+        assert_eq!(perft(&bd, 3), 97862);
+    }
+}
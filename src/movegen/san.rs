@@ -0,0 +1,200 @@
+//! Standard Algebraic Notation (SAN) for moves
+
+use super::*;
+
+/// A legal move together with everything a UI might want to show about it
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotatedMove {
+    pub m: Move,
+    /// The move in SAN (e.g. `Nbd7`, `exd6`, `O-O`, `e8=Q+`)
+    pub san: String,
+    pub gives_check: bool,
+    pub gives_mate: bool,
+    pub is_capture: bool,
+}
+
+impl AnnotatedMove {
+    /// Annotates a move that is legal on `board`
+    pub fn new(board: &Board, m: Move) -> AnnotatedMove {
+        let after = position_after(board, m);
+        let gives_mate = after.is_checkmate();
+        let gives_check = gives_mate || after.is_in_check();
+        let mut san = san_body(board, m);
+        if gives_mate {
+            san.push('#');
+        } else if gives_check {
+            san.push('+');
+        }
+        AnnotatedMove { m, san, gives_check, gives_mate, is_capture: m.capture }
+    }
+}
+
+impl Move {
+    /// Returns the move in Standard Algebraic Notation
+    ///
+    /// `board` is the position the move is played from, and the move must be
+    /// legal in it (SAN depends on which other moves are possible)
+    pub fn to_san(&self, board: &Board) -> String {
+        AnnotatedMove::new(board, *self).san
+    }
+
+    /// Parses a move in Standard Algebraic Notation, returning it if it is
+    /// legal on `board`
+    ///
+    /// Check and mate marks and annotations (`!`, `?`) are optional, and
+    /// castling may also be written with zeros (`0-0`). Ambiguous moves
+    /// (e.g. `Nd2` when two knights can go there) are rejected.
+    pub fn from_san(san: &str, board: &Board) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']).replace('0', "O");
+        generate_legal(board).into_iter().find(|&m| san_body(board, m) == san)
+    }
+}
+
+/// Helper function, returns the position after `m` from the point of view of
+/// the player who didn't make the move
+fn position_after(board: &Board, m: Move) -> Board {
+    let mut after = *board;
+    after.play(m);
+    after
+}
+
+/// Helper function, writes the SAN of a move without the check/mate suffix
+fn san_body(board: &Board, m: Move) -> String {
+    match m.castling {
+        Some(Side::K) => return String::from("O-O"),
+        Some(Side::Q) => return String::from("O-O-O"),
+        None => (),
+    }
+    let color = board.color();
+    let absolute = |sq: Square| match color {
+        Color::White => sq,
+        Color::Black => sq.flipped(),
+    };
+    if let Some(pt) = m.drop {
+        return format!("{}@{}", Piece::Pc(Whose::Ours, pt).to_char(), absolute(m.to));
+    }
+    let from = absolute(m.from).to_string();
+    let to = absolute(m.to).to_string();
+    let pt = m.piece;
+    let mut san = String::new();
+    if pt == PieceType::P {
+        if m.capture {
+            san.push_str(&from[..1]);
+            san.push('x');
+        }
+        san.push_str(&to);
+        if let Some(promo_pt) = m.promotion {
+            san.push('=');
+            san.push(Piece::Pc(Whose::Ours, promo_pt).to_char());
+        }
+        return san;
+    }
+    san.push(Piece::Pc(Whose::Ours, pt).to_char());
+    // Disambiguate between pieces of the same type reaching the same square
+    let rivals: Vec<Move> = generate_legal(board)
+        .into_iter()
+        .filter(|o| o.to == m.to && o.from != m.from && o.castling.is_none()
+                && o.drop.is_none() && board.get(o.from) == Piece::Pc(Whose::Ours, pt))
+        .collect();
+    if !rivals.is_empty() {
+        let same_file = rivals.iter().any(|o| o.from.file() == m.from.file());
+        let same_rank = rivals.iter().any(|o| o.from.rank() == m.from.rank());
+        if !same_file {
+            san.push_str(&from[..1]);
+        } else if !same_rank {
+            san.push_str(&from[1..]);
+        } else {
+            san.push_str(&from);
+        }
+    }
+    if m.capture {
+        san.push('x');
+    }
+    san.push_str(&to);
+    san
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::bits::File;
+
+    /// Helper function, finds the SAN of every legal move
+    fn all_san(board: &Board) -> Vec<String> {
+        generate_legal(board).iter().map(|m| m.to_san(board)).collect()
+    }
+
+    #[test]
+    fn test_to_san() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let san = all_san(&bd);
+        assert!(san.contains(&String::from("e4")));
+        assert!(san.contains(&String::from("Nf3")));
+        // Disambiguation by file, by rank, and promotions
+        bd.board_from_fen("1n2k3/P7/8/8/8/R7/8/R3K1N1 w - - 0 1");
+        let san = all_san(&bd);
+        assert!(san.contains(&String::from("R3a2")));
+        assert!(san.contains(&String::from("R1a2")));
+        assert!(san.contains(&String::from("axb8=Q+")));
+        assert!(san.contains(&String::from("a8=N")));
+        assert!(san.contains(&String::from("Ne2")));
+        bd.board_from_fen("4k3/8/8/8/8/8/8/R3K2R w KQ - 0 1");
+        let san = all_san(&bd);
+        assert!(san.contains(&String::from("O-O")));
+        assert!(san.contains(&String::from("O-O-O")));
+        // Squares are written from White's point of view for Black
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1");
+        let san = all_san(&bd);
+        assert!(san.contains(&String::from("Nc6")));
+        assert!(san.contains(&String::from("d5")));
+    }
+
+    #[test]
+    fn test_from_san() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("1n2k3/P7/8/8/8/R7/8/R3K2R w K - 0 1");
+        for m in generate_legal(&bd) {
+            assert_eq!(Move::from_san(&m.to_san(&bd), &bd), Some(m));
+        }
+        let find = |san: &str| Move::from_san(san, &bd).map(|m| m.to_uci(Color::White));
+        assert_eq!(find("axb8=Q"), Some(String::from("a7b8q")));
+        assert_eq!(find("axb8=Q+!?"), Some(String::from("a7b8q")));
+        assert_eq!(find("0-0"), Some(String::from("e1g1")));
+        assert_eq!(find("R3a2"), Some(String::from("a3a2")));
+        // Ambiguous, illegal, and malformed moves
+        assert_eq!(find("Ra2"), None);
+        assert_eq!(find("O-O-O"), None);
+        assert_eq!(find("Ke3"), None);
+        assert_eq!(find(""), None);
+        // Squares are read from White's point of view for Black
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1");
+        let m = Move::from_san("Nc6", &bd).unwrap();
+        assert_eq!(m.to_uci(Color::Black), "b8c6");
+    }
+
+    #[test]
+    fn test_annotated_moves() {
+        tables::init();
+        let mut bd = Board::new();
+        // Ra8 is mate and Rxh7 is a quiet capture
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5KR w - - 0 1");
+        let moves = bd.annotated_moves();
+        let find = |san: &str| moves.iter().find(|a| a.san == san).unwrap();
+        assert!(find("Ra8#").gives_mate);
+        assert!(find("Ra8#").gives_check);
+        assert!(!find("Ra8#").is_capture);
+        assert!(find("Rxh7").is_capture);
+        assert!(!find("Rxh7").gives_check);
+        // With an escape square Ra8 is only check
+        bd.board_from_fen("6k1/5p1p/8/8/8/8/8/R5K1 w - - 0 1");
+        let moves = bd.annotated_moves();
+        let check = moves.iter().find(|a| a.m.to == Square::from(File::A, Rank::Eighth)).unwrap();
+        assert_eq!(check.san, "Ra8+");
+        assert!(check.gives_check);
+        assert!(!check.gives_mate);
+    }
+}
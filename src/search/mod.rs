@@ -0,0 +1,377 @@
+//! Searching the game tree for the best move
+//!
+//! Scores follow the same convention as [`crate::eval`]: centipawns relative
+//! to the side to move.
+
+use std::time::{Duration, Instant};
+
+use crate::board::Board;
+use crate::eval;
+use crate::movegen::{Move, generate_legal};
+
+mod ordering;
+mod tt;
+
+use ordering::MoveOrdering;
+pub use tt::{Bound, TranspositionTable, TtEntry};
+
+/// Score for delivering checkmate right now
+///
+/// Mates further away score less (`MATE - ply`), so the search prefers the
+/// fastest mate and the slowest way to get mated
+pub const MATE: i32 = 30_000;
+
+/// Bound larger than any score
+const INFINITY: i32 = MATE + 1;
+
+/// Number of transposition table slots used by [`search`]
+pub const TT_SIZE: usize = 1 << 16;
+
+/// Scores beyond this (in either direction) are mates
+const MATE_BOUND: i32 = MATE - 1000;
+
+/// Deepest iteration [`search_timed`] will start
+pub const MAX_DEPTH: u32 = 64;
+
+/// Returns the number of moves (not plies) until mate for a mate score, or
+/// `None` for any other score
+/// 
+/// The number is negative when the side to move is the one getting mated,
+/// as in UCI's `score mate <moves>`
+pub fn score_to_mate_in(score: i32) -> Option<i32> {
+    if score > MATE_BOUND {
+        Some((MATE - score + 1) / 2)
+    } else if score < -MATE_BOUND {
+        Some(-(MATE + score + 1) / 2)
+    } else {
+        None
+    }
+}
+
+/// Searches `depth` plies deep and returns the best move with its score
+///
+/// A depth of zero is treated as one, since a move has to be picked.
+/// Panics if the side to move has no legal moves.
+pub fn search(board: &Board, depth: u32) -> (Move, i32) {
+    let mut tt = TranspositionTable::new(TT_SIZE);
+    search_with_tt(board, depth, &mut tt)
+}
+
+/// Like [`search`], but reads and fills the transposition table `tt`, so
+/// results from earlier searches (e.g. shallower iterations) are reused
+pub fn search_with_tt(board: &Board, depth: u32, tt: &mut TranspositionTable) -> (Move, i32) {
+    let mut state = SearchState::new(tt, None);
+    search_root(board, depth, &mut state).unwrap()
+}
+
+/// Everything a search carries from node to node
+struct SearchState<'a> {
+    tt: &'a mut TranspositionTable,
+    /// When to give up, if ever
+    deadline: Option<Instant>,
+    /// Killer moves and history, for ordering quiet moves
+    ordering: MoveOrdering,
+    /// Number of nodes visited by [`negamax`] (not counting quiescence)
+    nodes: u64,
+}
+
+impl<'a> SearchState<'a> {
+    /// Helper function, creates the state for a new search
+    fn new(tt: &'a mut TranspositionTable, deadline: Option<Instant>) -> SearchState<'a> {
+        SearchState { tt, deadline, ordering: MoveOrdering::new(), nodes: 0 }
+    }
+}
+
+/// Searches with iterative deepening (depth 1, 2, 3, ...) until `millis`
+/// milliseconds have passed and returns the best move of the deepest
+/// completed iteration
+///
+/// Each iteration reuses the transposition table, so the previous
+/// iteration's best move is searched first. Depth 1 is always completed,
+/// however short the budget. Panics if the side to move has no legal moves.
+pub fn search_timed(board: &Board, millis: u64) -> Move {
+    let deadline = Instant::now() + Duration::from_millis(millis);
+    let mut tt = TranspositionTable::new(TT_SIZE);
+    let mut state = SearchState::new(&mut tt, None);
+    let (mut best, _) = search_root(board, 1, &mut state).unwrap();
+    state.deadline = Some(deadline);
+    for depth in 2..=MAX_DEPTH {
+        // Killers from a shallower tree sit at the wrong plies
+        state.ordering.reset_killers();
+        match search_root(board, depth, &mut state) {
+            Some((m, _)) => best = m,
+            None => break,
+        }
+    }
+    best
+}
+
+/// Helper function, searches every root move, or returns `None` if the
+/// deadline passed before the search was done
+fn search_root(board: &Board, depth: u32, state: &mut SearchState) -> Option<(Move, i32)> {
+    let mut bd = *board;
+    let mut moves = generate_legal(&bd);
+    if moves.is_empty() {
+        panic!("Attempted to search a position without legal moves");
+    }
+    let depth = depth.max(1);
+    let hash = bd.hash();
+    let tt_best = state.tt.probe(hash).and_then(|e| e.best);
+    state.ordering.order_moves(&mut moves, tt_best, 0);
+    let mut best = (moves[0], -INFINITY);
+    let mut alpha = -INFINITY;
+    for m in moves {
+        let undo = bd.make(m);
+        let score = -negamax(&mut bd, state, depth - 1, 1, -INFINITY, -alpha);
+        bd.unmake(undo);
+        if out_of_time(state.deadline) {
+            return None;
+        }
+        if score > best.1 {
+            best = (m, score);
+        }
+        alpha = alpha.max(score);
+    }
+    state.tt.store(TtEntry { hash, depth, score: best.1, bound: Bound::Exact, best: Some(best.0) });
+    Some(best)
+}
+
+/// Helper function, returns whether or not `deadline` has passed
+fn out_of_time(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|d| Instant::now() >= d)
+}
+
+/// Helper function, negamax with alpha-beta pruning
+///
+/// `ply` is the distance from the root, used to score mates by distance and
+/// to look up killer moves. Once the deadline passes the search unwinds with
+/// a meaningless score, without storing anything in the transposition table.
+fn negamax(board: &mut Board, state: &mut SearchState, depth: u32, ply: i32,
+           mut alpha: i32, beta: i32) -> i32 {
+    state.nodes += 1;
+    let mut moves = generate_legal(board);
+    if moves.is_empty() {
+        return if board.is_in_check() { -(MATE - ply) } else { 0 };
+    }
+    if depth == 0 {
+        return quiescence(board, alpha, beta);
+    }
+    let hash = board.hash();
+    let entry = state.tt.probe(hash);
+    if let Some(e) = entry {
+        if e.depth >= depth {
+            let score = score_from_tt(e.score, ply);
+            match e.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return beta,
+                Bound::Upper if score <= alpha => return alpha,
+                _ => (),
+            }
+        }
+    }
+    // The best move found last time is the most likely to cause a cutoff
+    state.ordering.order_moves(&mut moves, entry.and_then(|e| e.best), ply);
+    let mut best = None;
+    for m in moves {
+        let undo = board.make(m);
+        let score = -negamax(board, state, depth - 1, ply + 1, -beta, -alpha);
+        board.unmake(undo);
+        if out_of_time(state.deadline) {
+            return 0;
+        }
+        if score >= beta {
+            state.ordering.record_cutoff(m, ply, depth);
+            let score = score_to_tt(beta, ply);
+            state.tt.store(TtEntry { hash, depth, score, bound: Bound::Lower, best: Some(m) });
+            return beta;
+        }
+        if score > alpha {
+            alpha = score;
+            best = Some(m);
+        }
+    }
+    let bound = if best.is_some() { Bound::Exact } else { Bound::Upper };
+    state.tt.store(TtEntry { hash, depth, score: score_to_tt(alpha, ply), bound, best });
+    alpha
+}
+
+/// Helper function, converts a mate score from "distance from the root" to
+/// "distance from this node" so it stays valid wherever the node is reached
+fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND {
+        score + ply
+    } else if score < -MATE_BOUND {
+        score - ply
+    } else {
+        score
+    }
+}
+
+/// Helper function, the inverse of [`score_to_tt`]
+fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score > MATE_BOUND {
+        score - ply
+    } else if score < -MATE_BOUND {
+        score + ply
+    } else {
+        score
+    }
+}
+
+/// Searches captures only until the position is quiet, so that the search
+/// doesn't stop in the middle of an exchange
+/// 
+/// The side to move may "stand pat" on the static evaluation instead of
+/// capturing, since it is rarely forced to capture
+pub fn quiescence(board: &mut Board, mut alpha: i32, beta: i32) -> i32 {
+    let stand_pat = eval::evaluate(board);
+    if stand_pat >= beta {
+        return beta;
+    }
+    alpha = alpha.max(stand_pat);
+    // The most promising captures first, to cut off as early as possible
+    for m in board.captures_ordered() {
+        let undo = board.make(m);
+        let score = -quiescence(board, -beta, -alpha);
+        board.unmake(undo);
+        if score >= beta {
+            return beta;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::bits::{File, Rank, Square};
+    use crate::helper::tables;
+
+    #[test]
+    fn test_search_mate_in_one() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let (m, score) = search(&bd, 2);
+        assert_eq!(m.to, Square::from(File::A, Rank::Eighth));
+        assert_eq!(score, MATE - 1);
+        bd.make(m);
+        assert!(bd.is_checkmate());
+        // The same mate for Black
+        bd.board_from_fen("r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1");
+        let (m, score) = search(&bd, 3);
+        assert_eq!(m.to_uci(bd.color()), "a8a1");
+        assert_eq!(score, MATE - 1);
+    }
+
+    #[test]
+    fn test_search_with_tt() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let mut tt = TranspositionTable::new(TT_SIZE);
+        let (m, score) = search_with_tt(&bd, 3, &mut tt);
+        let entry = tt.probe(bd.hash()).unwrap();
+        assert_eq!(entry.best, Some(m));
+        assert_eq!(entry.score, score);
+        assert_eq!(entry.depth, 3);
+        // Searching again with the filled table gives the same answer
+        assert_eq!(search_with_tt(&bd, 3, &mut tt), (m, score));
+        assert_eq!(search(&bd, 3), (m, score));
+        // Mates are still found through the table
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        for depth in 1..=3 {
+            assert_eq!(search_with_tt(&bd, depth, &mut tt).1, MATE - 1);
+        }
+    }
+
+    #[test]
+    fn test_search_timed() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        let start = Instant::now();
+        let m = search_timed(&bd, 200);
+        assert!(start.elapsed() < Duration::from_millis(700));
+        assert!(generate_legal(&bd).contains(&m));
+        // Even without any time a move is found
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        assert_eq!(search_timed(&bd, 0).to, Square::from(File::A, Rank::Eighth));
+    }
+
+    #[test]
+    fn test_quiescence() {
+        tables::init();
+        let mut bd = Board::new();
+        // Qxd5 wins a pawn but loses the queen to cxd5
+        bd.board_from_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1");
+        let (m, score) = search(&bd, 1);
+        assert_ne!(m.to, Square::from(File::D, Rank::Fifth));
+        assert!(score > 0);
+        let qxd5 = generate_legal(&bd).into_iter()
+            .find(|m| m.to == Square::from(File::D, Rank::Fifth))
+            .unwrap();
+        bd.make(qxd5);
+        assert!(quiescence(&mut bd, -INFINITY, INFINITY) > 0);
+    }
+
+    #[test]
+    fn test_search_material() {
+        tables::init();
+        let mut bd = Board::new();
+        // Grab the hanging queen
+        bd.board_from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1");
+        let (m, score) = search(&bd, 1);
+        assert_eq!(m.to, Square::from(File::D, Rank::Fifth));
+        assert!(score > 0);
+        // Being stalemated is a draw, even a queen down
+        bd.board_from_fen("7k/8/6Q1/8/8/8/8/K7 b - - 0 1");
+        let mut tt = TranspositionTable::new(TT_SIZE);
+        let mut state = SearchState::new(&mut tt, None);
+        assert_eq!(negamax(&mut bd, &mut state, 2, 0, -INFINITY, INFINITY), 0);
+    }
+
+    #[test]
+    fn test_mate_distance() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+        let mate_in_one = search(&bd, 2).1;
+        bd.board_from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1");
+        let mate_in_two = search(&bd, 4).1;
+        assert!(mate_in_one > mate_in_two);
+        assert_eq!(score_to_mate_in(mate_in_one), Some(1));
+        assert_eq!(score_to_mate_in(mate_in_two), Some(2));
+        // Getting mated, from the other side of the board
+        assert_eq!(score_to_mate_in(-(MATE - 2)), Some(-1));
+        assert_eq!(score_to_mate_in(-(MATE - 4)), Some(-2));
+        assert_eq!(score_to_mate_in(-MATE), Some(0));
+        assert_eq!(score_to_mate_in(150), None);
+        assert_eq!(score_to_mate_in(-150), None);
+    }
+
+    #[test]
+    fn test_killers_history() {
+        tables::init();
+        let mut bd = Board::new();
+        bd.board_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3");
+        let mut nodes = Vec::new();
+        let mut results = Vec::new();
+        for ordering in [MoveOrdering::disabled(), MoveOrdering::new()] {
+            let mut tt = TranspositionTable::new(TT_SIZE);
+            let mut state = SearchState { ordering, ..SearchState::new(&mut tt, None) };
+            let mut result = None;
+            for depth in 1..=4 {
+                state.ordering.reset_killers();
+                result = search_root(&bd, depth, &mut state);
+            }
+            nodes.push(state.nodes);
+            results.push(result.unwrap().1);
+        }
+        // Better ordering prunes more, without changing the score
+        assert!(nodes[1] < nodes[0], "{:?}", nodes);
+        assert_eq!(results[0], results[1]);
+    }
+}
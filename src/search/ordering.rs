@@ -0,0 +1,159 @@
+//! Move ordering for the search: the transposition table move, then captures
+//! by MVV-LVA, then killer moves, then the remaining quiet moves by history
+
+use crate::board::bits::Square;
+use crate::movegen::Move;
+
+/// Number of killer moves kept per ply
+const KILLERS_PER_PLY: usize = 2;
+
+/// Ordering score of the transposition table move
+const TT_MOVE: i32 = 3_000_000;
+
+/// Base ordering score of captures and promotions
+const CAPTURE: i32 = 2_000_000;
+
+/// Ordering score of the first killer move at a ply (the second scores one
+/// less)
+const KILLER: i32 = 1_000_000;
+
+/// History scores are halved once one passes this, to stay below the
+/// killers
+const HISTORY_LIMIT: i32 = KILLER / 2;
+
+/// Killer moves and the history heuristic, learned from beta cutoffs during
+/// a search
+///
+/// Killers are quiet moves that caused a cutoff at the same ply elsewhere in
+/// the tree, and are likely to do so again in sibling positions. History
+/// counts cutoffs by each quiet move's `[from][to]` squares anywhere in the
+/// tree.
+#[derive(Clone, Debug)]
+pub struct MoveOrdering {
+    killers: Vec<[Option<Move>; KILLERS_PER_PLY]>,
+    history: Box<[[i32; Square::COUNT]; Square::COUNT]>,
+    /// Whether or not killers and history are learned at all (without them
+    /// only the table move and MVV-LVA order the moves)
+    enabled: bool,
+}
+
+impl MoveOrdering {
+    /// Creates empty killer and history tables
+    pub fn new() -> MoveOrdering {
+        MoveOrdering {
+            killers: Vec::new(),
+            history: Box::new([[0; Square::COUNT]; Square::COUNT]),
+            enabled: true,
+        }
+    }
+
+    /// Creates tables that never learn anything, leaving only the table move
+    /// and MVV-LVA
+    #[cfg(test)]
+    pub fn disabled() -> MoveOrdering {
+        MoveOrdering { enabled: false, ..MoveOrdering::new() }
+    }
+
+    /// Forgets the killer moves (at the start of each iterative deepening
+    /// iteration), keeping the history
+    pub fn reset_killers(&mut self) -> () {
+        self.killers.clear();
+    }
+
+    /// Learns from quiet move `m` causing a beta cutoff at `ply`, searched
+    /// `depth` plies deep
+    ///
+    /// Captures and promotions are already ordered first, so they are not
+    /// recorded.
+    pub fn record_cutoff(&mut self, m: Move, ply: i32, depth: u32) -> () {
+        if !self.enabled || m.capture || m.promotion.is_some() {
+            return;
+        }
+        let ply = ply as usize;
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; KILLERS_PER_PLY]);
+        }
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(m) {
+            killers[1] = killers[0];
+            killers[0] = Some(m);
+        }
+        if let (Some(from), Some(to)) = (m.from.try_val(), m.to.try_val()) {
+            let entry = &mut self.history[from as usize][to as usize];
+            *entry += (depth * depth) as i32;
+            if *entry > HISTORY_LIMIT {
+                for row in self.history.iter_mut() {
+                    for score in row.iter_mut() {
+                        *score /= 2;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sorts `moves` at `ply` best first: `tt_best` (the transposition
+    /// table's move), captures and promotions by MVV-LVA, killers, and then
+    /// quiet moves by history
+    pub fn order_moves(&self, moves: &mut [Move], tt_best: Option<Move>, ply: i32) -> () {
+        moves.sort_by_cached_key(|&m| -self.score(m, tt_best, ply));
+    }
+
+    /// Helper function, returns how promising a move looks (higher first)
+    fn score(&self, m: Move, tt_best: Option<Move>, ply: i32) -> i32 {
+        if Some(m) == tt_best {
+            return TT_MOVE;
+        }
+        if m.capture || m.promotion.is_some() {
+            // Most valuable victim first, then least valuable attacker
+            let victim = m.captured.map_or(0, |pt| pt.value());
+            let promotion = m.promotion.map_or(0, |pt| pt.value());
+            return CAPTURE + 10 * (victim + promotion) - m.piece.value() / 10;
+        }
+        if let Some(killers) = self.killers.get(ply as usize) {
+            if let Some(i) = killers.iter().position(|&k| k == Some(m)) {
+                return KILLER - i as i32;
+            }
+        }
+        match (m.from.try_val(), m.to.try_val()) {
+            (Some(from), Some(to)) => self.history[from as usize][to as usize],
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::Board;
+    use crate::helper::tables;
+    use crate::movegen::generate_legal;
+
+    #[test]
+    fn test_order_moves() {
+        tables::init();
+        let mut bd = Board::new();
+        // Pawn and queen can both take the rook
+        bd.board_from_fen("4k3/8/8/2r1p3/1P6/8/8/2Q1K3 w - - 0 1");
+        let mut ordering = MoveOrdering::new();
+        let mut moves = generate_legal(&bd);
+        let quiets: Vec<Move> = moves.iter().filter(|m| !m.capture).copied().collect();
+        ordering.record_cutoff(quiets[0], 1, 3);
+        ordering.order_moves(&mut moves, None, 1);
+        let color = bd.color();
+        assert_eq!(moves[0].to_uci(color), "b4c5");
+        assert_eq!(moves[1].to_uci(color), "c1c5");
+        assert_eq!(moves[2], quiets[0]);
+        // The table move comes before everything
+        ordering.order_moves(&mut moves, Some(quiets[1]), 1);
+        assert_eq!(moves[0], quiets[1]);
+        // The newest killer comes first, but without killers the longer
+        // history wins
+        ordering.record_cutoff(quiets[1], 1, 1);
+        ordering.order_moves(&mut moves, None, 1);
+        assert_eq!(moves[2..4], [quiets[1], quiets[0]]);
+        ordering.reset_killers();
+        ordering.order_moves(&mut moves, None, 1);
+        assert_eq!(moves[2..4], [quiets[0], quiets[1]]);
+    }
+}
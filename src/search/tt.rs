@@ -0,0 +1,102 @@
+//! A transposition table caching search results by Zobrist hash
+
+use crate::movegen::Move;
+
+/// How a stored score relates to the true score of the position
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    /// The score is exact
+    Exact,
+    /// The search failed high, so the true score is at least this
+    Lower,
+    /// The search failed low, so the true score is at most this
+    Upper,
+}
+
+/// The result of searching a position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TtEntry {
+    /// Full hash of the position, to tell apart positions sharing a slot
+    pub hash: u64,
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best: Option<Move>,
+}
+
+/// A fixed-size table of search results indexed by `hash % size`
+#[derive(Clone, Debug)]
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+}
+
+impl TranspositionTable {
+    /// Creates a table with `size` slots
+    pub fn new(size: usize) -> TranspositionTable {
+        if size == 0 {
+            panic!("Attempted to create a TranspositionTable without any slots");
+        }
+        TranspositionTable { entries: vec![None; size] }
+    }
+
+    /// Returns the stored entry for the position with `hash`, if any
+    pub fn probe(&self, hash: u64) -> Option<TtEntry> {
+        match self.entries[self.index(hash)] {
+            Some(entry) if entry.hash == hash => Some(entry),
+            _ => None,
+        }
+    }
+
+    /// Stores an entry, keeping whichever of it and the slot's current entry
+    /// was searched deeper (ties go to the new entry)
+    pub fn store(&mut self, entry: TtEntry) -> () {
+        let i = self.index(entry.hash);
+        match self.entries[i] {
+            Some(old) if old.depth > entry.depth => (),
+            _ => self.entries[i] = Some(entry),
+        }
+    }
+
+    /// Empties every slot
+    pub fn clear(&mut self) -> () {
+        for entry in &mut self.entries {
+            *entry = None;
+        }
+    }
+
+    /// Helper function, returns the slot for `hash`
+    fn index(&self, hash: u64) -> usize {
+        (hash % self.entries.len() as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Helper function, makes an entry without a best move
+    fn entry(hash: u64, depth: u32, score: i32) -> TtEntry {
+        TtEntry { hash, depth, score, bound: Bound::Exact, best: None }
+    }
+
+    #[test]
+    fn test_probe_store() {
+        let mut tt = TranspositionTable::new(16);
+        assert_eq!(tt.probe(5), None);
+        tt.store(entry(5, 3, 42));
+        assert_eq!(tt.probe(5), Some(entry(5, 3, 42)));
+        // 21 shares a slot with 5 but is a different position
+        assert_eq!(tt.probe(21), None);
+        // A shallower search doesn't replace a deeper one...
+        tt.store(entry(21, 2, -7));
+        assert_eq!(tt.probe(5), Some(entry(5, 3, 42)));
+        assert_eq!(tt.probe(21), None);
+        // ...but an equally deep one does
+        tt.store(entry(21, 3, -7));
+        assert_eq!(tt.probe(21), Some(entry(21, 3, -7)));
+        assert_eq!(tt.probe(5), None);
+        tt.clear();
+        assert_eq!(tt.probe(21), None);
+    }
+}
@@ -0,0 +1,126 @@
+//! The Universal Chess Interface (UCI), for talking to chess GUIs
+//!
+//! Only the commands needed to play a game are supported: `uci`, `isready`,
+//! `ucinewgame`, `position`, `go` (with `movetime` or `depth`), and `quit`.
+//! Anything else is ignored, as the protocol asks.
+
+use std::io::{self, BufRead, Write};
+
+use crate::board::{Board, STARTPOS};
+use crate::helper::tables;
+use crate::movegen::{Move, generate_legal};
+use crate::search::{search, search_timed};
+
+/// Depth searched by `go` when it is given no limits
+const DEFAULT_DEPTH: u32 = 4;
+
+/// Runs the engine on stdin and stdout until `quit` (or the end of input)
+pub fn run() -> () {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    if let Err(e) = run_with(stdin.lock(), &mut stdout) {
+        eprintln!("UCI error: {}", e);
+    }
+}
+
+/// Runs the engine, reading commands from `input` and writing replies to
+/// `output`
+pub fn run_with<R: BufRead, W: Write>(input: R, output: &mut W) -> io::Result<()> {
+    tables::init();
+    let mut board = startpos();
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("uci") => {
+                writeln!(output, "id name rustmonkey {}", env!("CARGO_PKG_VERSION"))?;
+                writeln!(output, "id author mattngaw")?;
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("ucinewgame") => board = startpos(),
+            Some("position") => {
+                if let Some(bd) = parse_position(&line) {
+                    board = bd;
+                }
+            }
+            Some("go") => {
+                let best = go(&board, &line);
+                writeln!(output, "bestmove {}", best)?;
+            }
+            Some("quit") => break,
+            _ => (),
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+/// Helper function, returns the starting position
+fn startpos() -> Board {
+    let mut board = Board::new();
+    board.board_from_fen(STARTPOS);
+    board
+}
+
+/// Helper function, parses `position [startpos | fen <fen>] [moves <moves>]`
+///
+/// Returns `None` if the FEN is invalid or one of the moves is illegal
+fn parse_position(line: &str) -> Option<Board> {
+    let (setup, moves) = match line.split_once(" moves") {
+        Some((setup, moves)) => (setup, moves),
+        None => (line, ""),
+    };
+    let mut words = setup.split_whitespace().skip(1);
+    let mut board = match words.next()? {
+        "startpos" => startpos(),
+        "fen" => Board::from_fen(&words.collect::<Vec<_>>().join(" ")).ok()?,
+        _ => return None,
+    };
+    for uci in moves.split_whitespace() {
+        let m = Move::from_uci(uci, &board)?;
+        board.make(m);
+    }
+    Some(board)
+}
+
+/// Helper function, searches for the best move as `go` asks and returns it in
+/// UCI notation (`0000` if there are no legal moves)
+fn go(board: &Board, line: &str) -> String {
+    if generate_legal(board).is_empty() {
+        return String::from("0000");
+    }
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let arg = |name: &str| {
+        words.iter()
+            .position(|&w| w == name)
+            .and_then(|i| words.get(i + 1))
+            .and_then(|w| w.parse::<u64>().ok())
+    };
+    let best = match (arg("movetime"), arg("depth")) {
+        (Some(millis), _) => search_timed(board, millis),
+        (None, Some(depth)) => search(board, depth as u32).0,
+        (None, None) => search(board, DEFAULT_DEPTH).0,
+    };
+    best.to_uci(board.color())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::board::Color;
+
+    #[test]
+    fn test_parse_position() {
+        tables::init();
+        let bd = parse_position("position startpos moves e2e4 e7e5 g1f3").unwrap();
+        assert_eq!(bd.color(), Color::Black);
+        assert_eq!(bd.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2");
+        let fen = "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1";
+        let bd = parse_position(&format!("position fen {} moves e1c1", fen)).unwrap();
+        assert_eq!(bd.to_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+        assert!(parse_position("position startpos moves e2e5").is_none());
+        assert!(parse_position("position fen 8/8/8/8/8/8/8/8 w - - 0 1").is_none());
+    }
+}
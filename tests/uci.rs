@@ -0,0 +1,38 @@
+//! Drives the engine through a scripted UCI session
+
+use std::io::Cursor;
+
+use rustmonkey::uci;
+
+/// Helper function, feeds `script` to the engine and returns its replies
+fn session(script: &str) -> Vec<String> {
+    let mut output = Vec::new();
+    uci::run_with(Cursor::new(script), &mut output).unwrap();
+    String::from_utf8(output).unwrap().lines().map(String::from).collect()
+}
+
+#[test]
+fn test_uci_session() {
+    let replies = session("uci\nisready\nucinewgame\n\
+                           position startpos moves e2e4 e7e5\ngo depth 2\nquit\n");
+    assert!(replies.iter().any(|l| l.starts_with("id name rustmonkey")));
+    assert!(replies.contains(&String::from("uciok")));
+    assert!(replies.contains(&String::from("readyok")));
+    let bestmove = replies.iter().find(|l| l.starts_with("bestmove ")).unwrap();
+    assert_eq!(bestmove.len(), "bestmove g1f3".len());
+}
+
+#[test]
+fn test_uci_go() {
+    // Mate in one is found with either kind of limit
+    let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    for limit in ["depth 2", "movetime 50"] {
+        let replies = session(&format!("position fen {}\ngo {}\n", fen, limit));
+        assert_eq!(replies, vec![String::from("bestmove a1a8")]);
+    }
+    // Nothing is searched after quit
+    assert!(session("quit\ngo depth 1\n").is_empty());
+    // Checkmated, so there is no move to make
+    let replies = session("position startpos moves f2f3 e7e5 g2g4 d8h4\ngo depth 1\n");
+    assert_eq!(replies, vec![String::from("bestmove 0000")]);
+}